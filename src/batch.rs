@@ -0,0 +1,120 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+use treevolution::{CellType, Habitat, SimulationConfig, StatsRecorder};
+
+// runs the simulation without a window as fast as possible
+// usage: treevolution_batch [--config <path>] [--ticks <n>] [--seed <n>]
+//                           [--stats-interval <n>] [--minimum-plants <n>] [--output <dir>]
+struct BatchArgs {
+    config: SimulationConfig,
+    ticks: u64,
+    seed: Option<u64>,
+    stats_interval: u64,
+    minimum_plants: Option<usize>,
+    output: PathBuf,
+}
+
+impl BatchArgs {
+    fn parse() -> Result<Self, String> {
+        let mut out = BatchArgs {
+            config: SimulationConfig::default(),
+            ticks: 10_000,
+            seed: None,
+            stats_interval: 100,
+            minimum_plants: None,
+            output: PathBuf::from("batch_output"),
+        };
+        let mut args = std::env::args().skip(1);
+        while let Some(arg) = args.next() {
+            let value = args.next().ok_or(format!("missing value after {arg}"))?;
+            match arg.as_str() {
+                "--config" => out.config = SimulationConfig::load(&value)
+                    .map_err(|e| format!("{value}: {e}"))?,
+                "--ticks" => out.ticks = parse_number(&arg, &value)?,
+                "--seed" => out.seed = Some(parse_number(&arg, &value)?),
+                "--stats-interval" => out.stats_interval = parse_number(&arg, &value)?,
+                "--minimum-plants" => out.minimum_plants = Some(parse_number(&arg, &value)?),
+                "--output" => out.output = PathBuf::from(value),
+                _ => return Err(format!("unknown argument '{arg}'")),
+            }
+        }
+        if out.stats_interval == 0 {
+            return Err("--stats-interval has to be greater than 0".into());
+        }
+        Ok(out)
+    }
+}
+
+fn parse_number<T: std::str::FromStr>(arg: &str, value: &str) -> Result<T, String> {
+    value.parse().map_err(|_| format!("invalid number '{value}' for {arg}"))
+}
+
+fn main() {
+    let args = match BatchArgs::parse() {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("error: {e}");
+            std::process::exit(1);
+        }
+    };
+    if let Err(e) = run(args) {
+        eprintln!("error: {e}");
+        std::process::exit(1);
+    }
+}
+
+fn run(args: BatchArgs) -> Result<(), Box<dyn std::error::Error>> {
+    std::fs::create_dir_all(&args.output)?;
+
+    let grid_size = args.config.grid_size;
+    let mut habitat = match args.seed {
+        Some(seed) => Habitat::with_seed(args.config, seed)?,
+        None => Habitat::new(args.config)?,
+    };
+    habitat.set_minimum_plants(args.minimum_plants.unwrap_or((grid_size.x / 10) as usize));
+    println!("running {} ticks with seed {}", args.ticks, habitat.get_seed());
+
+    habitat.set_recorder(StatsRecorder::create(args.output.join("stats.csv"), args.stats_interval)?);
+
+    let start = Instant::now();
+    for _ in 0..args.ticks {
+        habitat.update();
+    }
+    habitat.finish_recording()?;
+
+    write_snapshot(&habitat, &args.output.join("final_snapshot.txt"))?;
+    habitat.save(args.output.join("final_habitat.json"))?;
+    // only the lineages which survived until the end
+    let phylogeny = habitat.get_phylogeny();
+    std::fs::write(args.output.join("phylogeny.nwk"), phylogeny.to_newick(true))?;
+    std::fs::write(args.output.join("phylogeny.json"), phylogeny.to_json(true))?;
+    std::fs::write(args.output.join("genome_stats.csv"), habitat.genome_stats().to_csv())?;
+
+    let elapsed = start.elapsed().as_secs_f64();
+    println!("finished in {elapsed:.2}s ({:.0} ticks per second)", args.ticks as f64 / elapsed);
+    Ok(())
+}
+
+// writes the grid as text, one character per cell, empty cells below the ground are written as #
+fn write_snapshot(habitat: &Habitat, path: &Path) -> std::io::Result<()> {
+    let mut file = BufWriter::new(File::create(path)?);
+    let cell_map = habitat.get_cell_map();
+    let top = habitat.get_grid_size().y - 1;
+    for row in 0..habitat.get_map_size().y as usize {
+        let line: String = cell_map.iter().zip(habitat.get_ground_height())
+            .map(|(column, ground)| match column[row] {
+                CellType::Empty if top - (row as i32) < *ground => '#',
+                CellType::Empty => '.',
+                CellType::Leaf { .. } => 'L',
+                CellType::Trunk { .. } => 'T',
+                CellType::Dead => 'D',
+                CellType::Seed => 'S',
+                CellType::Root { .. } => 'R',
+            })
+            .collect();
+        writeln!(file, "{line}")?;
+    }
+    file.flush()
+}
@@ -1,4 +1,5 @@
-use macroquad::math::IVec2;
+use glam::IVec2;
+use crate::sim_mod::genome::Crossover;
 
 pub const GRID_SIZE: IVec2 = IVec2::new(1024, 32);
 pub const ROOT_CON_DECAY: f32 = 0.8;        // how fast the connection to the root decays over distance
@@ -9,8 +10,17 @@ pub const LEAF_ABSORB_RATE: f32 = 0.4;      // how much sun gets absorbed by lea
 
 pub const MUTATION_RATE: f64 = 0.005;        // the probability that a genome value mutates
 
-pub const GENOME_SIZE: usize = 100;        // the size of genome raw data, unused data is intended
-                                            // -> changing this should have little to no effect
+pub const CREEP_RATE: f64 = 0.;             // the probability that a genome value gets changed by a small delta
+pub const CREEP_SIZE: u16 = 1024;           // the max delta of a creep mutation
+pub const DUPLICATION_RATE: f64 = 0.;       // the probability that an offspring duplicates a genome segment
+pub const INVERSION_RATE: f64 = 0.;         // the probability that an offspring reverses a genome segment
+pub const INSERTION_RATE: f64 = 0.;         // the probability that an offspring gets a random segment inserted
+pub const DELETION_RATE: f64 = 0.;          // the probability that an offspring looses a genome segment
+pub const MAX_SEGMENT_LENGTH: usize = 10;   // the max length of a duplicated, inverted, inserted or deleted segment
+pub const MAX_GENOME_LENGTH: usize = 2000;  // duplications and insertions can't grow a genome beyond this length
+
+pub const GENOME_SIZE: usize = 1000;       // the size of random genomes, only response_size * 20 + 1 values get decoded
+                                            // -> the remaining values are non-coding and evolve neutrally
 
 pub const RESPONSE_SIZE: usize = 20;       // the amount of response clusters a genome can hold
                                             // -> a response cluster holds all 4 responses for a cell
@@ -26,6 +36,53 @@ pub const DEFAULT_ENERGY: f32 = 125.;       // the default energy each plant sta
 
 pub const SUN_POWER: f32 = 4.;
 
+pub const SUN_ANGLE: f32 = 0.;              // the angle of the sun rays at noon in degrees, 0 shines straight down
+pub const DAY_LENGTH: u64 = 0;              // the amount of updates of a whole day, 0 means it is always noon
+pub const NIGHT_FRACTION: f32 = 0.5;        // the share of a day without any sunlight
+pub const SUN_ANGLE_SWEEP: f32 = 60.;       // how far the angle of the sun differs from noon at sunrise and sunset
+
+pub const YEAR_LENGTH: u64 = 0;             // the amount of updates of a whole year, 0 disables the seasons
+pub const SEASON_SUN_AMPLITUDE: f32 = 0.5;  // the sun power changes by this share between summer and winter
+pub const SEASON_SUSTAIN_AMPLITUDE: f32 = 0.25; // the sustain cost of cells changes by this share, it is highest in winter
+pub const GERMINATION_THRESHOLD: f32 = -1.; // seeds only grow a root while the season is at least this value
+pub const WINTER_THRESHOLD: f32 = -0.5;     // the season is winter while it is below this value
+pub const WINTER_LEAF_LOSS: f32 = 0.;       // the chance of every leaf to die in each update of the winter
+
+pub const TRUNK_LOAD_CAPACITY: f32 = 0.;    // the amount of cells a trunk next to the root can carry, 0 disables gravity
+                                            // -> the capacity decays with the root connection of the trunk
+pub const OVERHANG_LOAD_FACTOR: f32 = 2.;   // how much heavier cells weigh when they aren't supported from below
+
+pub const TERRAIN_HEIGHT: i32 = 0;          // the max height of the ground above the bottom of the grid, 0 keeps the ground flat
+pub const TERRAIN_HILLS: u32 = 3;           // the amount of big hills along the grid
+pub const TERRAIN_CLIFFS: u32 = 2;          // the amount of plateaus and basins, which have cliffs on both sides
+
+pub const SOIL_DEPTH: i32 = 0;              // the amount of soil rows below the ground, 0 disables roots and nutrients
+pub const SOIL_CAPACITY: f32 = 1.;          // the max amount of nutrients a soil cell can hold
+pub const SOIL_REGENERATION: f32 = 0.01;    // the nutrients each soil cell regains per update
+pub const ROOT_UPTAKE_RATE: f32 = 0.1;      // the max amount of nutrients a root cell draws from its soil cell per update
+pub const CELL_NUTRIENT_COST: f32 = 1.;     // the nutrients needed to grow a leaf or trunk, roots only cost energy
+pub const SEED_NUTRIENTS: f32 = 5.;         // the nutrients each seed starts with
+pub const DECAY_NUTRIENTS: f32 = 0.5;       // the nutrients a decaying dead cell releases into the ground beneath it
+pub const LITTER_SEEP_RATE: f32 = 0.05;     // the share of the litter on the ground which seeps into the top soil row per update
+pub const SEED_LITTER_UPTAKE: f32 = 0.1;    // the max amount of litter nutrients a grounded seed absorbs per update
+
+pub const RAIN_RATE: f32 = 0.05;            // the chance of rain in each update, lower values mean droughts
+pub const RAIN_AMOUNT: f32 = 2.;            // the water each rain puts on the ground of every column
+pub const INFILTRATION_RATE: f32 = 0.2;     // the max amount of water which seeps from the ground into the soil per update
+pub const MOISTURE_CAPACITY: f32 = 1.;      // the max amount of water a soil cell can hold
+pub const MOISTURE_DIFFUSION: f32 = 0.1;    // the share of the moisture difference which flows to each neighbor per update
+pub const EVAPORATION_RATE: f32 = 0.01;     // the share of the water on the ground and in the top soil row which evaporates
+pub const ROOT_WATER_UPTAKE: f32 = 0.05;    // the max amount of water a root cell draws from its soil cell per update
+pub const LEAF_WATER_DEMAND: f32 = 0.02;    // the water each leaf needs per update
+pub const DROUGHT_ENERGY_COST: f32 = 10.;   // the energy a plant looses per missing unit of water
+pub const DROUGHT_LEAF_LOSS: f32 = 0.05;    // the chance of a leaf to die, if the plant gets no water at all
+
+pub const WIND_SPEED: f32 = 0.;             // the cells airborne seeds get pushed per update at the top of the grid, negative blows to the left
+pub const WIND_GUST_STRENGTH: f32 = 0.;     // gusts change the wind speed of each update by a random amount up to this value
+pub const WIND_SHEAR: f32 = 0.;             // how much weaker the wind is near the ground, 1 means no wind at the ground at all
+pub const WING_GLIDE: f32 = 0.;             // seeds with full wings stay this many times longer in the air
+pub const WING_ENERGY_COST: f32 = 0.;       // the energy a seed with full wings has less at the start
+
 pub const SEED_ENERGY_DRAIN: f32 = 0.1;      // the amount of energy each seed looses while staying dormant in the ground
 
 pub const DEAD_CELL_REMOVE_RATE: f32 = 0.02; // the chance that a dead cell disappears
@@ -36,4 +93,16 @@ pub const BASE_MAX_AGE: usize = 40;         // the default max age of a plant
 
 pub const MAX_AGE_CELL_MODIFIER: usize = 7; // the amount each grown cell increases the lifetime of the plant
 
-pub const LIFETIME_FACTOR: f32 = 0.5;        // how much the lifetime of a plant increases the energy of seeds
\ No newline at end of file
+pub const LIFETIME_FACTOR: f32 = 0.5;        // how much the lifetime of a plant increases the energy of seeds
+
+pub const SPECIES_THRESHOLD: f32 = 0.1;     // the genome distance at which plants belong to different species
+
+pub const SPECIES_INTERVAL: u64 = 50;       // the amount of updates between two species clusterings
+
+pub const CROSSOVER: Crossover = Crossover::None; // how seeds get combined with a partner, None is asexual reproduction
+
+pub const CROSSOVER_POINTS: usize = 2;      // the amount of crossover points for Crossover::MultiPoint
+
+pub const POLLINATION_RANGE: i32 = 5;       // the max horizontal distance between the roots of two partners
+
+pub const POLLINATION_RATE: f32 = 1.;       // chance that a seed gets pollinated, if there is a partner in range
@@ -3,7 +3,6 @@ use macroquad::math::{IVec2, Vec2};
 use macroquad::shapes::{draw_line, draw_rectangle};
 use ndarray::{Array, Ix2};
 use rayon::iter::{IndexedParallelIterator};
-use crate::constants::simulation::GRID_SIZE;
 
 pub struct GridWindow {
     grid_size: IVec2,
@@ -159,7 +158,7 @@ impl GridWindow {
     }
 
     pub fn update_cells(&mut self, array: &[u8]) {
-        assert_eq!(array.len(), (GRID_SIZE.x * GRID_SIZE.y * 3) as usize);
+        assert_eq!(array.len(), (self.grid_size.x * self.grid_size.y * 3) as usize);
 
         let data = self.grid_content.as_slice_mut().unwrap();
         array.chunks(3).zip(data).for_each(|(i, color)| {
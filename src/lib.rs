@@ -1,12 +1,27 @@
-use crate::sim_mod::habitat::Habitat;
+pub use glam::IVec2;
+pub use crate::sim_mod::genome::{Crossover, Genome, GenomeError};
+pub use crate::sim_mod::genome_report::{ClusterReport, GenomeReport, ResponseReport};
+pub use crate::sim_mod::genome_stats::{GenomeStats, PositionStats};
+pub use crate::sim_mod::habitat::Habitat;
+pub use crate::sim_mod::habitat_stats::HabitatStats;
+pub use crate::sim_mod::lineage::Lineage;
+pub use crate::sim_mod::phylogeny::{Phylogeny, PhylogenyNode};
+pub use crate::sim_mod::plant_info::{PlantInfo, PlantState};
+pub use crate::sim_mod::plant_program::ProgramError;
 pub use crate::sim_mod::cell_types::CellType;
+pub use crate::sim_mod::simulation_config::{ConfigError, SimulationConfig};
+pub use crate::sim_mod::snapshot::SnapshotError;
+pub use crate::sim_mod::species::SpeciesInfo;
+pub use crate::sim_mod::stats_recorder::StatsRecorder;
+#[cfg(feature = "viewer")]
 pub use crate::traits::color_convert::ColorConvert;
 
 mod sim_mod;
 mod constants;
 
+#[cfg(feature = "viewer")]
 mod traits;
 
-pub fn create_habitat(grid_size: (i32, i32)) -> Habitat {
-    Habitat::new(grid_size.into())
+pub fn create_habitat(config: SimulationConfig) -> Result<Habitat, ConfigError> {
+    Habitat::new(config)
 }
\ No newline at end of file
@@ -1,16 +1,14 @@
 mod grid_window;
-mod traits;
-mod constants;
-mod sim_mod;
 
 use macroquad::input::KeyCode::*;
 use macroquad::prelude::*;
-use sim_mod::habitat::Habitat;
-use crate::constants::simulation::GRID_SIZE;
+use treevolution::{Genome, Habitat, SimulationConfig, StatsRecorder};
 use crate::grid_window::GridWindow;
 
 pub const UPDATES_PER_SECOND: u32 = 60;
 const TIME_PER_UPDATE: f32 = 1f32 / UPDATES_PER_SECOND as f32;
+const SNAPSHOT_PATH: &str = "habitat_snapshot.json";    // F5 saves the habitat here, F9 loads it
+const GENOME_PATH: &str = "plant_genome.hex";           // E exports the genome of the selected plant here, I plants it
 
 fn get_conf() -> Conf {
     Conf {
@@ -26,25 +24,75 @@ fn get_conf() -> Conf {
     }
 }
 
+// usage: treevolution_viewer [--config <path>] [--record <path>] [--record-interval <n>]
+// without a config file the default config is used
+struct ViewerArgs {
+    config: SimulationConfig,
+    record: Option<String>,
+    record_interval: u64,
+}
+
+impl ViewerArgs {
+    fn parse() -> Result<Self, String> {
+        let mut out = ViewerArgs {
+            config: SimulationConfig::default(),
+            record: None,
+            record_interval: 1,
+        };
+        let mut args = std::env::args().skip(1);
+        while let Some(arg) = args.next() {
+            let value = args.next().ok_or(format!("missing value after {arg}"))?;
+            match arg.as_str() {
+                "--config" => out.config = SimulationConfig::load(&value)
+                    .map_err(|e| format!("{value}: {e}"))?,
+                "--record" => out.record = Some(value),
+                "--record-interval" => out.record_interval = match value.parse() {
+                    Ok(n) if n > 0 => n,
+                    _ => return Err(format!("invalid record interval '{value}'")),
+                },
+                _ => return Err(format!("unknown argument '{arg}'")),
+            }
+        }
+        Ok(out)
+    }
+}
+
+fn exit_with_error(e: impl std::fmt::Display) -> ! {
+    eprintln!("error: {e}");
+    std::process::exit(1);
+}
+
 #[macroquad::main(get_conf())]
 async fn main() {
+    let args = ViewerArgs::parse().unwrap_or_else(|e| exit_with_error(e));
+    let grid_size = args.config.grid_size;
+
     let mut grid_dim = Vec2::new(screen_width(), screen_height() / 2.);
     let mut grid_pos = Vec2::new(0., screen_height() / 4.);
 
-    // responsible for drawing the habitat data
-    let mut grid = GridWindow::new(GRID_SIZE);
-
     // responsible for simulation
-    let mut habitat = Habitat::new(GRID_SIZE);
-    habitat.set_minimum_plants((GRID_SIZE.x / 10) as usize);
+    let mut habitat = Habitat::new(args.config).unwrap_or_else(|e| exit_with_error(e));
+
+    // responsible for drawing the habitat data, including the soil below the ground
+    let mut grid = GridWindow::new(habitat.get_map_size());
+    habitat.set_minimum_plants((grid_size.x / 10) as usize);
+    if let Some(path) = &args.record {
+        let recorder = StatsRecorder::create(path, args.record_interval)
+            .unwrap_or_else(|e| exit_with_error(format!("{path}: {e}")));
+        habitat.set_recorder(recorder);
+    }
+
+    // closing the window only ends the loop, so the recording can be finished
+    prevent_quit();
 
     let mut lag = 0.;
     let mut counter = 0;
     let mut running = true;
+    let mut species_colors = false;
 
     loop {
         counter += 1;
-        if is_key_down(Escape) { break }
+        if is_key_down(Escape) || is_quit_requested() { break }
 
         lag += get_frame_time();
 
@@ -60,6 +108,58 @@ async fn main() {
             if is_key_down(Right) { grid.change_offset(-20f32) }
             if is_key_pressed(Enter) { habitat.spawn_plant() }
             if is_key_pressed(Space) { running = !running }
+            if is_key_pressed(C) { species_colors = !species_colors }
+            if is_key_pressed(G) {
+                // prints the decoded genome of the selected plant
+                if let Some(report) = habitat.get_selected_plant()
+                    .and_then(|plant| habitat.get_genome_report(plant.lineage.id)) {
+                    println!("{}", report.to_text());
+                }
+            }
+            if is_key_pressed(E) {
+                if let Some(genome) = habitat.get_selected_plant()
+                    .and_then(|plant| habitat.get_genome(plant.lineage.id)) {
+                    match genome.save(GENOME_PATH) {
+                        Ok(()) => println!("saved genome to {GENOME_PATH}"),
+                        Err(e) => eprintln!("error: {e}"),
+                    }
+                }
+            }
+            if is_key_pressed(I) {
+                // plants the genome below the selected plant or in the middle of the grid
+                let x = habitat.get_selected_plant()
+                    .map_or(habitat.get_grid_size().x / 2, |plant| plant.pos.x);
+                match Genome::load(GENOME_PATH) {
+                    Ok(genome) => {
+                        let id = habitat.spawn_plant_with_genome(genome, x);
+                        println!("planted genome from {GENOME_PATH} as plant {id} at column {x}");
+                    }
+                    Err(e) => eprintln!("error: {e}"),
+                }
+            }
+            if is_key_pressed(F5) {
+                match habitat.save(SNAPSHOT_PATH) {
+                    Ok(()) => println!("saved habitat to {SNAPSHOT_PATH}"),
+                    Err(e) => eprintln!("error: {e}"),
+                }
+            }
+            if is_key_pressed(F9) {
+                match Habitat::load(SNAPSHOT_PATH) {
+                    Ok(mut loaded) => {
+                        // the snapshot might have a different grid size than the current habitat
+                        if loaded.get_map_size() != habitat.get_map_size() {
+                            grid = GridWindow::new(loaded.get_map_size());
+                        }
+                        // keep recording with the loaded habitat
+                        if let Some(recorder) = habitat.take_recorder() {
+                            loaded.set_recorder(recorder);
+                        }
+                        habitat = loaded;
+                        println!("loaded habitat from {SNAPSHOT_PATH}");
+                    }
+                    Err(e) => eprintln!("error: {e}"),
+                }
+            }
             if is_mouse_button_pressed(MouseButton::Left) {
                 if let Some(pos) =
                     grid.world_to_grid(&grid_pos, &grid_dim, mouse_position().into()) {
@@ -72,7 +172,11 @@ async fn main() {
                 counter = 0;
             }
 
-            grid.update_cells(habitat.get_rgb_data().as_slice());
+            if species_colors {
+                grid.update_cells(habitat.get_species_rgb_data().as_slice());
+            } else {
+                grid.update_cells(habitat.get_rgb_data().as_slice());
+            }
         }
 
         clear_background(GRAY);
@@ -82,4 +186,8 @@ async fn main() {
 
         next_frame().await;
     }
+
+    if let Err(e) = habitat.finish_recording() {
+        exit_with_error(e);
+    }
 }
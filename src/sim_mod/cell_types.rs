@@ -1,34 +1,49 @@
+#[cfg(feature = "viewer")]
 use macroquad::color::*;
-use crate::constants::simulation::LEAF_ABSORB_RATE;
-use crate::sim_mod::cell_types::CellType::{Leaf, Empty, Trunk, Seed, Dead};
+use serde::{Deserialize, Serialize};
+use crate::sim_mod::cell_types::CellType::{Leaf, Root, Trunk};
+#[cfg(feature = "viewer")]
+use crate::sim_mod::cell_types::CellType::{Empty, Seed, Dead};
+#[cfg(feature = "viewer")]
 use crate::traits::color_convert::ColorConvert;
 
 
+#[cfg(feature = "viewer")]
 const DEAD_LEAF_COLOR: Color = Color {
     r: 0.729,
     g: 0.557,
     b: 0.137,
     a: 1.,
 };
+#[cfg(feature = "viewer")]
 const DEAD_CELL_COLOR: Color = Color {
     r: 0.498,
     g: 0.439,
     b: 0.325,
     a: 1.,
 };
-#[derive(Copy, Clone)]
+#[cfg(feature = "viewer")]
+const ROOT_COLOR: Color = Color {
+    r: 0.878,
+    g: 0.784,
+    b: 0.588,
+    a: 1.,
+};
+#[derive(Copy, Clone, Serialize, Deserialize)]
 pub enum CellType {
     Empty,
-    Leaf { sun_absorbed: f32},
+    Leaf { sun_absorbed: f32, exposure: f32 }, // exposure is the share of sunlight reaching the leaf
     Trunk {root_connection: f32},
     Dead,
-    Seed
+    Seed,
+    Root {root_connection: f32},    // grows below the ground and draws nutrients from the soil
 }
 
 impl CellType {
     pub fn new_leaf() -> CellType {
         Leaf {
             sun_absorbed: 1.,
+            exposure: 1.,
         }
     }
     // creates a trunk which has full connection to root
@@ -39,29 +54,30 @@ impl CellType {
     }
 
     pub fn get_root_con(&self) -> f32 {
-        if let Trunk { root_connection, .. } = self {
+        if let Trunk { root_connection, .. } | Root { root_connection } = self {
             *root_connection
         } else {
-            panic!("Tried to get root con from a non Trunk CellType");
+            panic!("Tried to get root con from a non Trunk or Root CellType");
         }
     }
 }
 
+#[cfg(feature = "viewer")]
 impl ColorConvert for CellType {
     fn get_color(&self) -> Color {
         match self {
             Empty => {SKYBLUE}
-            Leaf { mut sun_absorbed } => {
-                sun_absorbed /= LEAF_ABSORB_RATE;
+            Leaf { exposure, .. } => {
                 Color::new(
-                    LIME.r * sun_absorbed + DEAD_LEAF_COLOR.r * (1.-sun_absorbed),
-                    LIME.g * sun_absorbed + DEAD_LEAF_COLOR.g * (1.-sun_absorbed),
-                    LIME.b * sun_absorbed + DEAD_LEAF_COLOR.b * (1.-sun_absorbed),
+                    LIME.r * exposure + DEAD_LEAF_COLOR.r * (1.-exposure),
+                    LIME.g * exposure + DEAD_LEAF_COLOR.g * (1.-exposure),
+                    LIME.b * exposure + DEAD_LEAF_COLOR.b * (1.-exposure),
                     1.)
             }
             Trunk { .. } => {BROWN}
             Seed => {YELLOW}
             Dead => {GRAY}
+            Root { .. } => {ROOT_COLOR}
         }
     }
 }
@@ -1,28 +1,50 @@
-use rand::{random, random_bool};
-use rayon::iter::{IntoParallelIterator, IntoParallelRefIterator, ParallelIterator};
-use crate::constants::simulation::MUTATION_RATE;
+use std::fmt::{Display, Formatter, Write};
+use std::ops::Range;
+use std::path::Path;
+use rand::Rng;
+use serde::{de, Deserialize, Deserializer, Serialize};
+use crate::sim_mod::simulation_config::SimulationConfig;
 
-const GENOME_SIZE: usize = 1000;
+// how the genomes of two parents get combined, None means asexual reproduction
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Crossover {
+    None,
+    SinglePoint,    // the values after a random point come from the partner
+    MultiPoint,     // every second segment between random points comes from the partner
+    Uniform,        // every value comes from either parent with the same chance
+}
 
-#[derive(Default)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Genome {
+    #[serde(deserialize_with = "deserialize_data")]
     data: Vec<u16>,
     value_counter: usize,
 }
 
+// parsing wraps around the data, so a genome loaded from a snapshot needs at least one value as well
+fn deserialize_data<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u16>, D::Error> {
+    let data = Vec::<u16>::deserialize(deserializer)?;
+    if data.is_empty() {
+        return Err(de::Error::custom(GenomeError::Empty));
+    }
+    Ok(data)
+}
+
 impl Genome {
-    pub fn new() -> Genome {
-        let data: Vec<u16> = (0..GENOME_SIZE).into_par_iter().map(|_| random()).collect();
-        Genome { data, ..Default::default() }
+    // the genome is generated sequentially, so the result only depends on the given rng
+    pub fn new(config: &SimulationConfig, rng: &mut impl Rng) -> Genome {
+        let data: Vec<u16> = (0..config.genome_size).map(|_| rng.random()).collect();
+        Genome { data, value_counter: 0 }
     }
 
     fn parse_values(&mut self, amount: usize) -> Vec<u16> {
         let mut values = Vec::<u16>::with_capacity(amount);
         // check if we need to wrap around
-        if (self.value_counter + amount) > GENOME_SIZE {
-            values = (self.value_counter..GENOME_SIZE)
+        if (self.value_counter + amount) > self.data.len() {
+            values = (self.value_counter..self.data.len())
                 .into_iter().chain(
-                0..(amount - (GENOME_SIZE - self.value_counter)))
+                0..(amount - (self.data.len() - self.value_counter)))
                 .map(|x| self.data[x])
                 .collect();
         } else {
@@ -31,19 +53,18 @@ impl Genome {
                 .map(|x| self.data[x])
                 .collect();
         }
-        self.value_counter =(self.value_counter + amount) % GENOME_SIZE;
+        self.value_counter =(self.value_counter + amount) % self.data.len();
         values
     }
 
+    // genomes can have any length, so parsing wraps around at the end
     pub fn parse_value(&mut self) -> u16 {
-        let out = self.data[self.value_counter];
+        let out = self.data[self.value_counter % self.data.len()];
         self.value_counter = self.value_counter + 1;
         out
     }
     pub fn parse_value_normalized(&mut self) -> f32 {
-        let out = self.data[self.value_counter];
-        self.value_counter = self.value_counter + 1;
-        out as f32 / u16::MAX as f32
+        self.parse_value() as f32 / u16::MAX as f32
     }
 
     pub fn parse_bool(&mut self) -> bool {
@@ -51,16 +72,276 @@ impl Genome {
         out >= 0.5
     }
 
-    pub fn create_offspring(&self) -> Genome {
-        let data = self.data.par_iter().map(|x| Self::mutate(&x)).collect();
-        Genome { data, ..Default::default() }
+    // the share of genome values which differ between both genomes, between 0 and 1
+    pub fn distance(&self, other: &Genome) -> f32 {
+        let length = self.data.len().max(other.data.len());
+        if length == 0 {
+            return 0.;
+        }
+        let equal = self.data.iter().zip(other.data.iter()).filter(|(a, b)| a == b).count();
+        (length - equal) as f32 / length as f32
+    }
+
+    // combines this genome with the genome of a partner, the child keeps the length of this genome
+    pub fn crossover(&self, partner: &Genome, config: &SimulationConfig, rng: &mut impl Rng) -> Genome {
+        let length = self.data.len().min(partner.data.len());
+        let mut data = self.data.clone();
+        match config.crossover {
+            Crossover::None => {}
+            Crossover::SinglePoint => {
+                let point = rng.random_range(0..=length);
+                data[point..length].copy_from_slice(&partner.data[point..length]);
+            }
+            Crossover::MultiPoint => {
+                let mut points: Vec<usize> = (0..config.crossover_points)
+                    .map(|_| rng.random_range(0..=length))
+                    .collect();
+                points.sort_unstable();
+                // an odd amount of points takes the rest of the genome from the partner
+                for segment in points.chunks(2) {
+                    let end = segment.get(1).copied().unwrap_or(length);
+                    data[segment[0]..end].copy_from_slice(&partner.data[segment[0]..end]);
+                }
+            }
+            Crossover::Uniform => {
+                for (value, partner_value) in data.iter_mut().zip(&partner.data) {
+                    if rng.random_bool(0.5) {
+                        *value = *partner_value;
+                    }
+                }
+            }
+        }
+        Genome { data, value_counter: 0 }
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    // decoding reads the values sequentially from the start, so every position before the
+    // value counter is coding, a genome shorter than the decoded amount is coding completely
+    pub fn get_coding_map(&self) -> Vec<bool> {
+        (0..self.data.len()).map(|pos| pos < self.value_counter).collect()
+    }
+
+    // creates a genome from raw values, for example to plant a genome from an earlier run
+    pub fn from_values(data: Vec<u16>) -> Result<Genome, GenomeError> {
+        if data.is_empty() {
+            return Err(GenomeError::Empty);
+        }
+        Ok(Genome { data, value_counter: 0 })
+    }
+
+    pub fn get_values(&self) -> &[u16] {
+        &self.data
+    }
+
+    // starts parsing from the beginning again
+    pub fn rewind(&mut self) {
+        self.value_counter = 0;
+    }
+
+    // 4 hex digits per value
+    pub fn to_hex(&self) -> String {
+        let mut out = String::with_capacity(self.data.len() * 4);
+        for value in &self.data {
+            write!(out, "{value:04x}").unwrap();
+        }
+        out
+    }
+
+    // whitespace is ignored, so long hex strings can be wrapped
+    pub fn from_hex(hex: &str) -> Result<Genome, GenomeError> {
+        let digits: Vec<char> = hex.chars().filter(|c| !c.is_whitespace()).collect();
+        if !digits.len().is_multiple_of(4) {
+            return Err(GenomeError::Parse(format!("expected 4 hex digits per value, got {} digits", digits.len())));
+        }
+        let data = digits.chunks(4)
+            .map(|chunk| {
+                let value: String = chunk.iter().collect();
+                u16::from_str_radix(&value, 16).map_err(|_| GenomeError::Parse(format!("invalid hex value '{value}'")))
+            })
+            .collect::<Result<Vec<u16>, GenomeError>>()?;
+        Self::from_values(data)
+    }
+
+    // a json array of the values
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(&self.data).unwrap()
+    }
+
+    pub fn from_json(json: &str) -> Result<Genome, GenomeError> {
+        let data: Vec<u16> = serde_json::from_str(json).map_err(|e| GenomeError::Parse(e.to_string()))?;
+        Self::from_values(data)
+    }
+
+    // saves the genome to a .hex or .json file, the format is chosen by the file extension
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), GenomeError> {
+        let path = path.as_ref();
+        let content = match GenomeFormat::from_path(path)? {
+            GenomeFormat::Hex => self.to_hex(),
+            GenomeFormat::Json => self.to_json(),
+        };
+        std::fs::write(path, content)?;
+        Ok(())
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Genome, GenomeError> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)?;
+        match GenomeFormat::from_path(path)? {
+            GenomeFormat::Hex => Self::from_hex(&content),
+            GenomeFormat::Json => Self::from_json(&content),
+        }
+    }
+
+    // every point or creep mutation which changes a value gets counted at its position in mutation_counts
+    pub fn create_offspring(&self, config: &SimulationConfig, rng: &mut impl Rng, mutation_counts: &mut Vec<u64>) -> Genome {
+        if mutation_counts.len() < self.data.len() {
+            mutation_counts.resize(self.data.len(), 0);
+        }
+        let mut data: Vec<u16> = self.data.iter().zip(mutation_counts.iter_mut())
+            .map(|(x, count)| {
+                let value = Self::mutate(x, config, rng);
+                if value != *x {
+                    *count += 1;
+                }
+                value
+            })
+            .collect();
+        // each segment operator happens at most once per offspring
+        if rng.random_bool(config.duplication_rate) {
+            Self::duplicate_segment(&mut data, config, rng);
+        }
+        if rng.random_bool(config.inversion_rate) {
+            Self::invert_segment(&mut data, config, rng);
+        }
+        if rng.random_bool(config.insertion_rate) {
+            Self::insert_segment(&mut data, config, rng);
+        }
+        if rng.random_bool(config.deletion_rate) {
+            Self::delete_segment(&mut data, config, rng);
+        }
+        Genome { data, value_counter: 0 }
     }
 
-    fn mutate(val: &u16) -> u16 {
-        if random_bool(MUTATION_RATE) {
-            random()
+    // a value is either replaced by a random one, shifted by a small delta (creep) or kept
+    fn mutate(val: &u16, config: &SimulationConfig, rng: &mut impl Rng) -> u16 {
+        if rng.random_bool(config.mutation_rate) {
+            rng.random()
+        } else if rng.random_bool(config.creep_rate) {
+            let creep_size = config.creep_size as i32;
+            (*val as i32 + rng.random_range(-creep_size..=creep_size)).clamp(0, u16::MAX as i32) as u16
         } else {
             *val
         }
     }
-}
\ No newline at end of file
+
+    // a random segment of at most max_segment_length values which lies completely inside the data
+    fn random_segment(data: &[u16], config: &SimulationConfig, rng: &mut impl Rng) -> Range<usize> {
+        let length = rng.random_range(1..=config.max_segment_length.min(data.len()));
+        let start = rng.random_range(0..=data.len() - length);
+        start..start + length
+    }
+
+    // the copy gets inserted right behind the original segment
+    fn duplicate_segment(data: &mut Vec<u16>, config: &SimulationConfig, rng: &mut impl Rng) {
+        if data.is_empty() {
+            return;
+        }
+        let segment = Self::random_segment(data, config, rng);
+        if data.len() + segment.len() <= config.max_genome_length {
+            let copy = data[segment.clone()].to_vec();
+            data.splice(segment.end..segment.end, copy);
+        }
+    }
+
+    fn invert_segment(data: &mut [u16], config: &SimulationConfig, rng: &mut impl Rng) {
+        if data.is_empty() {
+            return;
+        }
+        let segment = Self::random_segment(data, config, rng);
+        data[segment].reverse();
+    }
+
+    fn insert_segment(data: &mut Vec<u16>, config: &SimulationConfig, rng: &mut impl Rng) {
+        let length = rng.random_range(1..=config.max_segment_length);
+        let pos = rng.random_range(0..=data.len());
+        if data.len() + length <= config.max_genome_length {
+            let values: Vec<u16> = (0..length).map(|_| rng.random()).collect();
+            data.splice(pos..pos, values);
+        }
+    }
+
+    // a genome never gets deleted completely
+    fn delete_segment(data: &mut Vec<u16>, config: &SimulationConfig, rng: &mut impl Rng) {
+        if data.is_empty() {
+            return;
+        }
+        let segment = Self::random_segment(data, config, rng);
+        if segment.len() < data.len() {
+            data.drain(segment);
+        }
+    }
+}
+
+enum GenomeFormat {
+    Hex,
+    Json,
+}
+
+impl GenomeFormat {
+    fn from_path(path: &Path) -> Result<Self, GenomeError> {
+        match path.extension().and_then(|x| x.to_str()) {
+            Some("hex") => Ok(GenomeFormat::Hex),
+            Some("json") => Ok(GenomeFormat::Json),
+            _ => Err(GenomeError::UnknownFormat(path.display().to_string())),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum GenomeError {
+    Io(std::io::Error),
+    Parse(String),
+    UnknownFormat(String),
+    Empty,
+}
+
+impl Display for GenomeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GenomeError::Io(e) => write!(f, "could not access genome file: {e}"),
+            GenomeError::Parse(e) => write!(f, "could not parse genome: {e}"),
+            GenomeError::UnknownFormat(path) =>
+                write!(f, "unknown genome format of '{path}', expected a .hex or .json file"),
+            GenomeError::Empty => write!(f, "a genome needs at least 1 value"),
+        }
+    }
+}
+
+impl std::error::Error for GenomeError {}
+
+impl From<std::io::Error> for GenomeError {
+    fn from(e: std::io::Error) -> Self {
+        GenomeError::Io(e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_genomes_are_rejected() {
+        assert!(matches!(Genome::from_values(Vec::new()), Err(GenomeError::Empty)));
+        assert!(matches!(Genome::from_json("[]"), Err(GenomeError::Empty)));
+        assert!(serde_json::from_str::<Genome>(r#"{"data":[],"value_counter":0}"#).is_err());
+        let genome: Genome = serde_json::from_str(r#"{"data":[1,2],"value_counter":1}"#).unwrap();
+        assert_eq!(genome.get_values(), &[1, 2]);
+    }
+}
@@ -0,0 +1,126 @@
+use std::fmt::Write;
+use serde::Serialize;
+use crate::sim_mod::cell_types::CellType;
+use crate::sim_mod::response_cluster::ResponseCluster;
+use crate::sim_mod::simulation_config::SimulationConfig;
+
+// the directions of the 4 responses of a cluster, in the order they are stored
+const DIRECTIONS: [&str; 4] = ["up", "right", "down", "left"];
+const DOWN: usize = 2;
+
+// the decoded program of a plant, what each response cluster grows in which direction
+#[derive(Clone, Debug, Serialize)]
+pub struct GenomeReport {
+    pub plant_id: u64,
+    pub genome_length: usize,
+    pub wing_size: f32,
+    pub clusters: Vec<ClusterReport>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct ClusterReport {
+    pub index: usize,
+    pub reachable: bool,    // the root cell uses cluster 0, trunks grown from there lead to the other reachable clusters
+    pub root_reachable: bool,   // used by root cells, only possible with soil rows below the ground
+    pub responses: Vec<ResponseReport>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct ResponseReport {
+    pub direction: &'static str,
+    pub cell_type: &'static str,    // the cell type above the ground
+    pub grows_roots: bool,          // the response grows a root when it reaches below the ground, whatever its cell type
+    pub target_cluster: usize,      // the cluster the new cell uses, only matters for trunks
+    pub height_threshold: i32,      // 0 means the response is active at any height
+    pub growth_bias_factor: f32,
+    pub min_energy: f32,            // the energy the plant needs for this response next to the root
+}
+
+impl GenomeReport {
+    pub fn new(plant_id: u64, genome_length: usize, clusters: &[ResponseCluster], wing_size: f32,
+               config: &SimulationConfig) -> Self {
+        let soil = config.soil_depth > 0;
+        let (reachable, root_reachable) = Self::find_reachable(clusters, soil);
+        let clusters = clusters.iter().enumerate()
+            .map(|(index, cluster)| ClusterReport {
+                index,
+                reachable: reachable[index],
+                root_reachable: root_reachable[index],
+                responses: cluster.get_responses().iter().zip(DIRECTIONS).enumerate()
+                    .map(|(direction_ix, (response, direction))| ResponseReport {
+                        direction,
+                        cell_type: match response.get_cell_type() {
+                            CellType::Leaf { .. } => "leaf",
+                            CellType::Trunk { .. } => "trunk",
+                            _ => "other",
+                        },
+                        grows_roots: root_reachable[index] || (soil && reachable[index] && direction_ix == DOWN),
+                        target_cluster: response.get_cluster_index(),
+                        height_threshold: response.get_height_threshold(),
+                        growth_bias_factor: response.get_growth_bias_factor(),
+                        min_energy: config.cell_growth_cost / response.get_growth_bias_factor(),
+                    })
+                    .collect(),
+            })
+            .collect();
+        Self { plant_id, genome_length, wing_size, clusters }
+    }
+
+    // walks the trunk responses starting at cluster 0, leaves never grow so their clusters are never used.
+    // with soil everything growing below the ground becomes a root which keeps growing with its cluster,
+    // trunks reach the soil by growing down, roots by growing in any direction
+    fn find_reachable(clusters: &[ResponseCluster], soil: bool) -> (Vec<bool>, Vec<bool>) {
+        let mut reachable = vec![false; clusters.len()];
+        let mut root_reachable = vec![false; clusters.len()];
+        let mut stack = vec![(0, false)];
+        while let Some((index, is_root)) = stack.pop() {
+            let visited = if is_root { &mut root_reachable } else { &mut reachable };
+            if index >= clusters.len() || visited[index] {
+                continue;
+            }
+            visited[index] = true;
+            for (direction_ix, response) in clusters[index].get_responses().iter().enumerate() {
+                let target = response.get_cluster_index();
+                if is_root || (soil && direction_ix == DOWN) {
+                    stack.push((target, true));
+                }
+                if !is_root && matches!(response.get_cell_type(), CellType::Trunk { .. }) {
+                    stack.push((target, false));
+                }
+            }
+        }
+        (reachable, root_reachable)
+    }
+
+    pub fn to_text(&self) -> String {
+        let reachable_count = self.clusters.iter().filter(|cluster| cluster.reachable || cluster.root_reachable).count();
+        let mut out = String::new();
+        writeln!(out, "plant {}, genome length {}, {} clusters ({} reachable from the root)",
+                 self.plant_id, self.genome_length, self.clusters.len(), reachable_count).unwrap();
+        writeln!(out, "wing size {:.3}", self.wing_size).unwrap();
+        for cluster in &self.clusters {
+            let usage = match (cluster.reachable, cluster.root_reachable) {
+                (true, false) => "",
+                (true, true) => " (also used by roots)",
+                (false, true) => " (only used by roots)",
+                (false, false) => " (unreachable)",
+            };
+            writeln!(out, "cluster {}{usage}", cluster.index).unwrap();
+            for response in &cluster.responses {
+                let height = match response.height_threshold {
+                    0 => "any height".to_string(),
+                    h => format!("height >= {h}"),
+                };
+                writeln!(out, "  {:<5} {:<5} -> cluster {:<3} {:<12} bias {:<8.3} min energy {:.3}{}",
+                         response.direction, response.cell_type, response.target_cluster, height,
+                         response.growth_bias_factor, response.min_energy,
+                         if response.grows_roots { ", a root below the ground" } else { "" }).unwrap();
+            }
+        }
+        out
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("a genome report can always be serialized")
+    }
+}
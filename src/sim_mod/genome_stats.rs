@@ -0,0 +1,67 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+use serde::{Deserialize, Serialize};
+use crate::sim_mod::genome::Genome;
+
+// per position statistics of the genomes of all living plants,
+// positions are compared by index, so segment mutations shift values to other positions
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct GenomeStats {
+    pub genome_count: usize,
+    pub positions: Vec<PositionStats>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct PositionStats {
+    pub coding_share: f32,  // the share of genomes which read this position while decoding
+    pub mutations: u64,     // point and creep mutations at this position over the whole run
+    pub fixation: f32,      // the share of genomes with the most common value at this position
+}
+
+impl GenomeStats {
+    pub fn new(genomes: &[&Genome], mutation_counts: &[u64]) -> Self {
+        let length = genomes.iter().map(|genome| genome.len()).max().unwrap_or(0);
+        let coding_maps: Vec<Vec<bool>> = genomes.iter().map(|genome| genome.get_coding_map()).collect();
+        let positions = (0..length)
+            .map(|pos| {
+                // genomes which are too short don't count for this position
+                let mut value_counts = HashMap::<u16, usize>::new();
+                let mut coding = 0;
+                for (genome, coding_map) in genomes.iter().zip(&coding_maps) {
+                    if let Some(value) = genome.get_values().get(pos) {
+                        *value_counts.entry(*value).or_default() += 1;
+                        coding += coding_map[pos] as usize;
+                    }
+                }
+                let present: usize = value_counts.values().sum();
+                PositionStats {
+                    coding_share: coding as f32 / present as f32,
+                    mutations: mutation_counts.get(pos).copied().unwrap_or(0),
+                    fixation: value_counts.values().max().copied().unwrap_or(0) as f32 / present as f32,
+                }
+            })
+            .collect();
+        Self { genome_count: genomes.len(), positions }
+    }
+
+    // the mean fixation of the coding and of the non-coding positions,
+    // selection should keep the coding positions more uniform than the neutral ones
+    pub fn mean_fixation(&self) -> (f32, f32) {
+        let mean = |coding: bool| {
+            let values: Vec<f32> = self.positions.iter()
+                .filter(|x| (x.coding_share >= 0.5) == coding)
+                .map(|x| x.fixation)
+                .collect();
+            if values.is_empty() { 0. } else { values.iter().sum::<f32>() / values.len() as f32 }
+        };
+        (mean(true), mean(false))
+    }
+
+    pub fn to_csv(&self) -> String {
+        let mut out = String::from("position,coding_share,mutations,fixation\n");
+        for (pos, stats) in self.positions.iter().enumerate() {
+            writeln!(out, "{pos},{},{},{}", stats.coding_share, stats.mutations, stats.fixation).unwrap();
+        }
+        out
+    }
+}
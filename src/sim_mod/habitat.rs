@@ -1,63 +1,193 @@
 use std::collections::HashMap;
-use crate::constants::simulation::{CELL_SUSTAIN_ENERGY_COST, DEAD_CELL_REMOVE_RATE, LEAF_ABSORB_RATE, MAX_GROWTHS_PER_ITERATION, SEED_ENERGY_DRAIN, SEED_SPAWN_RATE, SUN_POWER, TRUNK_ABSORB_RATE};
 use crate::sim_mod::cell_types::CellType;
-use crate::sim_mod::cell_types::CellType::{Empty, Leaf, Trunk, Dead};
+use crate::sim_mod::cell_types::CellType::{Empty, Leaf, Trunk, Dead, Root};
+use crate::sim_mod::genome::{Crossover, Genome};
+use crate::sim_mod::genome_report::GenomeReport;
+use crate::sim_mod::genome_stats::GenomeStats;
+use crate::sim_mod::habitat_rng::{HabitatRng, RngStage};
+use crate::sim_mod::habitat_stats::HabitatStats;
+use crate::sim_mod::lineage::Lineage;
+use crate::sim_mod::phylogeny::Phylogeny;
 use crate::sim_mod::plant::Plant;
+use crate::sim_mod::plant_info::{PlantInfo, PlantState};
+use crate::sim_mod::simulation_config::{ConfigError, SimulationConfig};
+use crate::sim_mod::species::{SpeciesInfo, SpeciesTracker};
+use crate::sim_mod::stats_recorder::StatsRecorder;
+use crate::sim_mod::terrain::generate_terrain;
+#[cfg(feature = "viewer")]
 use crate::traits::color_convert::ColorConvert;
-use macroquad::math::IVec2;
-use rand::{random_bool, Rng};
-use rayon::iter::{IntoParallelRefMutIterator, ParallelIterator};
-use macroquad::rand::{gen_range};
+use glam::IVec2;
+use rand::Rng;
+use rayon::iter::{IndexedParallelIterator, IntoParallelRefIterator, IntoParallelRefMutIterator, ParallelIterator};
+use serde::{Deserialize, Serialize};
 
 // control struct, to hold the data of every tree and information of whole grid
+#[derive(Serialize, Deserialize)]
 pub struct Habitat {
+    config: SimulationConfig,
+    rng: HabitatRng,
     grid_size: IVec2,
     cell_map: Vec<Vec<CellType>>,
+    ground_height: Vec<i32>,    // plants root at this height in every column, the cells below it are soil
     plants: Vec<Plant>,
     seeds: Vec<Plant>,
     dead_cells: Vec<IVec2>,
+    soil: Vec<Vec<f32>>,    // the nutrients of every soil cell, column by column from the ground downwards,
+                            // besides the soil rows each column holds the cells of the terrain above the bottom of the grid
+    moisture: Vec<Vec<f32>>,    // the water of every soil cell, same layout as the soil
+    surface_water: Vec<f32>,    // rain water on the ground of every column, which didn't seep into the soil yet
+    litter: Vec<f32>,           // nutrients of decayed cells on the ground of every column
+    wind: f32,                  // the wind speed at the top of the grid in the current update, including the gust
     ground_buffer: Vec<Vec<Plant>>,
     minimum_plants: usize,
     selected_pos: Option<IVec2>,
     selected_plant_ix: Option<usize>,
     selected_cell_ix: Option<usize>,
+    tick: u64,
+    next_plant_id: u64,
+    phylogeny: Phylogeny,
+    species: SpeciesTracker,
+    mutation_counts: Vec<u64>,  // point and creep mutations per genome position over the whole run
+    // counters of the last update, reset at the start of every update
+    births: usize,
+    broken_cells: usize,
+    deaths: usize,
+    drought_plants: usize,
+    raining: bool,
+    absorbed_sunlight: f32,
+    // output files are not part of the habitat state
+    #[serde(skip)]
+    recorder: Option<StatsRecorder>,
 }
 
 impl Habitat {
-    pub fn new(grid_size: IVec2) -> Self {
-        Self {
+    // uses the seed of the config, or a random one if the config has none
+    pub fn new(config: SimulationConfig) -> Result<Self, ConfigError> {
+        let seed = config.seed.unwrap_or_else(rand::random);
+        Self::with_seed(config, seed)
+    }
+
+    // the same seed and config always result in the same simulation,
+    // the config gets validated first, so invalid parameters can't panic in the middle of a run
+    pub fn with_seed(config: SimulationConfig, seed: u64) -> Result<Self, ConfigError> {
+        config.validate()?;
+        let grid_size = config.grid_size;
+        let map_height = (grid_size.y + config.soil_depth) as usize;
+        let mut rng = HabitatRng::new(seed);
+        let ground_height = generate_terrain(&config, rng.stage(RngStage::Terrain));
+        // without soil rows there is no soil at all, the terrain only blocks light and growth
+        let soil_column = |ground: &i32, value: f32| match config.soil_depth {
+            0 => Vec::new(),
+            depth => vec![value; (ground + depth) as usize],
+        };
+        let soil = ground_height.iter().map(|ground| soil_column(ground, config.soil_capacity)).collect();
+        let moisture = ground_height.iter().map(|ground| soil_column(ground, config.moisture_capacity)).collect();
+        Ok(Self {
+            config,
+            rng,
             grid_size,
-            cell_map: vec![vec![Empty; grid_size.y as usize]; grid_size.x as usize],
+            cell_map: vec![vec![Empty; map_height]; grid_size.x as usize],
+            ground_height,
             plants: Vec::new(),
             seeds: Vec::new(),
             dead_cells: Vec::new(),
+            soil,
+            moisture,
+            surface_water: vec![0.; grid_size.x as usize],
+            litter: vec![0.; grid_size.x as usize],
+            wind: 0.,
             ground_buffer: {
                 // cant use macro here because plant is not clone
                 let mut out = vec![];
-                out.resize_with(grid_size.x as usize, || Vec::new());
+                out.resize_with(grid_size.x as usize, Vec::new);
                 out
             },
             minimum_plants: 0,
             selected_pos: None,
             selected_plant_ix: None,
             selected_cell_ix: None,
-        }
+            tick: 0,
+            next_plant_id: 0,
+            phylogeny: Phylogeny::default(),
+            species: SpeciesTracker::default(),
+            mutation_counts: Vec::new(),
+            births: 0,
+            broken_cells: 0,
+            deaths: 0,
+            drought_plants: 0,
+            raining: false,
+            absorbed_sunlight: 0.,
+            recorder: None,
+        })
     }
 
     // get the data of the grid as a linear rgb byte vector, every 3 bytes represent one pixel (rgb)
-    // the data is ordered column by column
+    // the data is ordered column by column, empty soil cells get darker the less nutrients they hold
+    // and bluer the more water they hold
+    #[cfg(feature = "viewer")]
     pub fn get_rgb_data(&self) -> Vec<u8> {
+        let top = self.grid_size.y - 1;
         self.cell_map
             .iter()
-            .flatten()
-            .map(|x| x.get_color())
+            .zip(self.soil.iter().zip(&self.moisture).zip(&self.ground_height))
+            .flat_map(move |(column, ((soil, moisture), ground))| column.iter().enumerate().map(move |(row, cell)| {
+                // the soil index counts from the ground downwards
+                let soil_ix = ground - 1 - (top - row as i32);
+                match cell {
+                    // the terrain without soil rows has no nutrients
+                    Empty if soil_ix >= 0 && soil.is_empty() => macroquad::color::DARKBROWN,
+                    Empty if soil_ix >= 0 => {
+                        let share = soil[soil_ix as usize] / self.config.soil_capacity.max(f32::EPSILON);
+                        let wetness = 0.5 * moisture[soil_ix as usize] / self.config.moisture_capacity.max(f32::EPSILON);
+                        let brightness = 0.4 + 0.6 * share;
+                        let soil_color = macroquad::color::DARKBROWN;
+                        let water_color = macroquad::color::DARKBLUE;
+                        let mix = |soil: f32, water: f32| soil * brightness * (1. - wetness) + water * wetness;
+                        macroquad::color::Color::new(mix(soil_color.r, water_color.r), mix(soil_color.g, water_color.g),
+                                                     mix(soil_color.b, water_color.b), 1.)
+                    }
+                    _ => cell.get_color(),
+                }
+            }))
             .flat_map(|x| [x.r, x.g, x.b])
             .map(|x| (x * 255.0) as u8)
             .collect::<Vec<u8>>()
     }
 
+    // same layout as get_rgb_data, but the cells of plants are colored by their species,
+    // leaves in a light and trunks in a dark shade. Plants without species keep their normal color
+    #[cfg(feature = "viewer")]
+    pub fn get_species_rgb_data(&self) -> Vec<u8> {
+        let mut data = self.get_rgb_data();
+        // trunks first, so leaves have priority like in apply_plants
+        for leaves in [false, true] {
+            for plant in &self.plants {
+                let Some(species_id) = plant.get_species_id() else { continue };
+                // spreading the hues with the golden ratio keeps neighbouring ids apart
+                let hue = (species_id as f32 * 0.618_034).fract();
+                for (pos, cell) in plant.get_cells() {
+                    let color = match cell {
+                        Leaf { .. } if leaves => macroquad::color::hsl_to_rgb(hue, 0.8, 0.6),
+                        Trunk { .. } if !leaves => macroquad::color::hsl_to_rgb(hue, 0.8, 0.3),
+                        _ => continue,
+                    };
+                    let x = ((pos.x + self.grid_size.x) % self.grid_size.x) as usize;
+                    let y = (self.grid_size.y - 1 - pos.y) as usize;
+                    let ix = (x * self.get_map_size().y as usize + y) * 3;
+                    data[ix..ix + 3].copy_from_slice(&[color.r, color.g, color.b].map(|x| (x * 255.0) as u8));
+                }
+            }
+        }
+        data
+    }
+
     // main update loop, meant to be called in a loop
     pub fn update(&mut self) {
+        self.tick += 1;
+        self.births = 0;
+        self.deaths = 0;
+        self.broken_cells = 0;
+        self.drought_plants = 0;
 
         // check if below minimum plants, if so spawn single plant
         if self.get_total_plant_count() < self.minimum_plants {
@@ -70,28 +200,69 @@ impl Habitat {
         // let each plant grow by the selected growths
         let plant_growth = self.create_growths();
         for (idx, growths) in plant_growth.into_iter().enumerate() {
-            let mut plant = &mut self.plants[idx];
             for (pos,response_ix, cell_type, energy_cost) in growths {
+                let nutrient_cost = self.get_nutrient_cost(cell_type);
+                let plant = &mut self.plants[idx];
                 plant.add_cell(pos, cell_type, response_ix);
                 plant.give_energy(-energy_cost);
+                plant.give_nutrients(-nutrient_cost);
+            }
+        }
+
+        // overloaded branches break off and fall apart into dead cells
+        let config = &self.config;
+        let broken_cells: Vec<Vec<IVec2>> = self.plants
+            .par_iter_mut()
+            .map(|plant| plant.break_overloaded(config))
+            .collect();
+        for cells in broken_cells {
+            self.broken_cells += cells.len();
+            self.dead_cells.extend(cells);
+        }
+
+        // rain seeps into the soil and spreads, roots draw water and plants without enough of it dry out,
+        // like the winter leaf loss this happens before the grid gets rebuilt from the plant cells
+        self.update_water();
+        self.supply_plant_water();
+
+        // a harsh winter kills leaves, they stay as dead cells until they decay
+        if self.config.winter_leaf_loss > 0. && self.get_season() < self.config.winter_threshold {
+            let rng = self.rng.stage(RngStage::Season);
+            let leaf_loss = self.config.winter_leaf_loss as f64;
+            for plant in &mut self.plants {
+                self.dead_cells.extend(plant.remove_leaves(|| rng.random_bool(leaf_loss)));
             }
         }
 
         // collect all indices of dead plants
-        let mut dead_plant_ix = Vec::<usize>::new();
-        for (ix, plant) in self.plants.iter().enumerate() {
-            if plant.is_dead() {
-                dead_plant_ix.push(ix);
-                //
-                for cell_ix in 0..plant.get_cells().len() {
-                    if random_bool(SEED_SPAWN_RATE as f64) {
-                        let (pos, _) = plant.get_cells()[cell_ix];
-                        self.seeds.push(plant.new_offspring(pos));
-                    }
+        let dead_plant_ix: Vec<usize> = (0..self.plants.len())
+            .filter(|ix| self.plants[*ix].is_dead(&self.config))
+            .collect();
+        for &ix in &dead_plant_ix {
+            self.phylogeny.record_death(self.plants[ix].get_lineage().id, self.tick);
+            // every cell of a dead plant above the ground can become a seed
+            for cell_ix in 0..self.plants[ix].get_cells().len() {
+                if let (_, Root { .. }) = self.plants[ix].get_cells()[cell_ix] {
+                    continue;
+                }
+                if self.rng.stage(RngStage::Offspring).random_bool(self.config.seed_spawn_rate as f64) {
+                    let partner = match self.config.crossover {
+                        Crossover::None => None,
+                        _ => self.find_pollinator(ix),
+                    };
+                    let (pos, _) = self.plants[ix].get_cells()[cell_ix];
+                    let seed = self.plants[ix].new_offspring(
+                        pos, &self.config, self.next_plant_id, self.tick,
+                        partner.as_ref().map(|(id, genome)| (*id, genome)), &mut self.mutation_counts);
+                    self.next_plant_id += 1;
+                    self.phylogeny.record_birth(*seed.get_lineage());
+                    self.seeds.push(seed);
                 }
             }
         }
 
+        self.deaths = dead_plant_ix.len();
+
         // collect all cell positions of dead plants
         for ix in dead_plant_ix {
             for (pos, _) in self.plants[ix].get_cells().iter() {
@@ -100,7 +271,7 @@ impl Habitat {
         }
 
         // destroy all plants with no energy or which are too old
-        self.plants.retain(|plant| !plant.is_dead());
+        self.plants.retain(|plant| !plant.is_dead(&self.config));
 
         // add all plant_cells to fresh habitat grid
         self.apply_plants();
@@ -111,13 +282,26 @@ impl Habitat {
         // look up the collected energy for each plant and add it while removing consumed energy
         self.supply_plant_energy();
 
-        // let dead cells "deteriorate"
-        self.dead_cells.retain(|_| !random_bool(DEAD_CELL_REMOVE_RATE as f64));
+        // roots draw nutrients from the soil, which regenerates afterwards
+        self.supply_plant_nutrients();
+
+        // let dead cells "deteriorate", their nutrients go back into the ground
+        let dead_cell_remove_rate = self.config.dead_cell_remove_rate as f64;
+        let rng = self.rng.stage(RngStage::Decay);
+        let mut decayed_cells = Vec::new();
+        self.dead_cells.retain(|pos| {
+            let decays = rng.random_bool(dead_cell_remove_rate);
+            if decays {
+                decayed_cells.push(*pos);
+            }
+            !decays
+        });
+        self.recycle_nutrients(decayed_cells);
 
         // update the selected plant and cell indices
         if let Some(selected_pos) = self.selected_pos {
             match self.get_cell_at(selected_pos) {
-                Leaf {..} | Trunk {..} => {
+                Leaf {..} | Trunk {..} | Root {..} => {
                     for (plant_ix, plant) in self.plants.iter().enumerate() {
                         if let Some(cell_ix) = plant.get_cell_ix_at(selected_pos) {
                             self.selected_plant_ix = Some(plant_ix);
@@ -144,17 +328,65 @@ impl Habitat {
 
         // adding all seeds to grid, happens after calculation because seeds cant impact anything
         self.show_seeds();
+
+        if self.config.species_interval > 0 && self.tick.is_multiple_of(self.config.species_interval) {
+            self.update_species();
+        }
+
+        if self.recorder.is_some() {
+            let stats = self.stats();
+            if let Some(recorder) = &mut self.recorder {
+                recorder.record(&stats);
+            }
+        }
     }
 
     // spawns a random seed in the grid
     pub fn spawn_plant(&mut self) {
-        let mut rng = rand::rng();
+        let rng = self.rng.stage(RngStage::Spawn);
         let pos = (rng.random_range(0..self.grid_size.x), self.grid_size.y-1);
+        let plant_seed = rng.random();
         if let Empty = self.get_cell_at(pos.into()) {
-            self.seeds.push(Plant::new(pos.into()));
+            let lineage = Lineage::new_origin(self.next_plant_id, self.tick);
+            self.next_plant_id += 1;
+            self.phylogeny.record_birth(lineage);
+            self.seeds.push(Plant::new(pos.into(), &self.config, plant_seed, lineage));
         }
     }
 
+    // plants a seed with the given genome into the ground at column x, it grows as soon as the column is free,
+    // x wraps around the grid and the id of the new plant is returned
+    pub fn spawn_plant_with_genome(&mut self, genome: Genome, x: i32) -> u64 {
+        let column = x.rem_euclid(self.grid_size.x);
+        let plant_seed = self.rng.stage(RngStage::Spawn).random();
+        let lineage = Lineage::new_origin(self.next_plant_id, self.tick);
+        self.next_plant_id += 1;
+        self.phylogeny.record_birth(lineage);
+        let ground = self.ground_height[column as usize];
+        let plant = Plant::new_with_genome(IVec2::new(column, ground), &self.config, plant_seed, lineage, genome);
+        self.ground_buffer[column as usize].push(plant);
+        lineage.id
+    }
+
+    // a copy of the genome of a living plant or seed, None if no plant with this id exists
+    pub fn get_genome(&self, id: u64) -> Option<Genome> {
+        self.iter_all_plants()
+            .find(|(plant, _)| plant.get_lineage().id == id)
+            .map(|(plant, _)| {
+                let mut genome = plant.get_genome().clone();
+                genome.rewind();
+                genome
+            })
+    }
+
+    pub fn get_seed(&self) -> u64 {
+        self.rng.get_seed()
+    }
+
+    pub fn get_config(&self) -> &SimulationConfig {
+        &self.config
+    }
+
     pub fn set_minimum_plants(&mut self, minimum_plants: usize) {
         self.minimum_plants = minimum_plants;
     }
@@ -166,6 +398,170 @@ impl Habitat {
             + self.ground_buffer.iter().map(|x| x.len()).sum::<usize>()
     }
 
+    // returns the amount of plants which have grown a root
+    pub fn get_living_plant_count(&self) -> usize {
+        self.plants.len()
+    }
+
+    pub fn get_grid_size(&self) -> IVec2 {
+        self.grid_size
+    }
+
+    // the size of the cell map, the grid above the ground and the soil rows below it
+    pub fn get_map_size(&self) -> IVec2 {
+        IVec2::new(self.grid_size.x, self.grid_size.y + self.config.soil_depth)
+    }
+
+    // the height of the ground in every column, plants root at this height
+    pub fn get_ground_height(&self) -> &[i32] {
+        &self.ground_height
+    }
+
+    // the nutrients of the soil cells, column by column from the ground downwards,
+    // columns with a higher ground hold more soil cells
+    pub fn get_soil(&self) -> &[Vec<f32>] {
+        &self.soil
+    }
+
+    // the nutrients of decayed cells on the ground of every column
+    pub fn get_litter(&self) -> &[f32] {
+        &self.litter
+    }
+
+    // the water of the soil cells, same layout as get_soil
+    pub fn get_moisture(&self) -> &[Vec<f32>] {
+        &self.moisture
+    }
+
+    // the cells of the grid, ordered column by column, each column from top to bottom including the soil rows
+    pub fn get_cell_map(&self) -> &[Vec<CellType>] {
+        &self.cell_map
+    }
+
+    // every following update gets recorded, a previous recorder is dropped
+    pub fn set_recorder(&mut self, recorder: StatsRecorder) {
+        self.recorder = Some(recorder);
+    }
+
+    // detaches the recorder without finishing it, e.g. to move it to another habitat
+    pub fn take_recorder(&mut self) -> Option<StatsRecorder> {
+        self.recorder.take()
+    }
+
+    // detaches the recorder and flushes it, has to be called before exiting to not lose any recorded stats
+    pub fn finish_recording(&mut self) -> std::io::Result<()> {
+        match self.recorder.take() {
+            Some(recorder) => recorder.finish(),
+            None => Ok(()),
+        }
+    }
+
+    // the amount of updates since the habitat was created
+    pub fn get_tick(&self) -> u64 {
+        self.tick
+    }
+
+    pub fn stats(&self) -> HabitatStats {
+        let grounded_seeds_per_column: Vec<usize> = self.ground_buffer.iter().map(|x| x.len()).collect();
+        let mut stats = HabitatStats {
+            tick: self.tick,
+            living_plants: self.plants.len(),
+            airborne_seeds: self.seeds.len(),
+            grounded_seeds: grounded_seeds_per_column.iter().sum(),
+            grounded_seeds_per_column,
+            species: self.species.get_species_count(),
+            absorbed_sunlight: self.absorbed_sunlight,
+            births: self.births,
+            deaths: self.deaths,
+            season: self.get_season(),
+            broken_cells: self.broken_cells,
+            soil_nutrients: self.soil.iter().flatten().fold(0., |sum, x| sum + x),
+            litter_nutrients: self.litter.iter().fold(0., |sum, x| sum + x),
+            soil_moisture: self.moisture.iter().flatten().fold(0., |sum, x| sum + x),
+            raining: self.raining,
+            drought_plants: self.drought_plants,
+            wind: self.wind,
+            ..Default::default()
+        };
+        let mut total_height = 0;
+        for plant in &self.plants {
+            stats.total_energy += plant.get_energy();
+            stats.max_energy = stats.max_energy.max(plant.get_energy());
+            total_height += plant.get_height();
+            for (_, cell) in plant.get_cells() {
+                match cell {
+                    Leaf { .. } => stats.leaf_cells += 1,
+                    Trunk { .. } => stats.trunk_cells += 1,
+                    Root { .. } => stats.root_cells += 1,
+                    _ => {}
+                }
+            }
+        }
+        if !self.plants.is_empty() {
+            stats.mean_wing_size = self.plants.iter().map(|plant| plant.get_wing_size()).sum::<f32>() / self.plants.len() as f32;
+            stats.mean_energy = stats.total_energy / self.plants.len() as f32;
+            stats.mean_plant_height = total_height as f32 / self.plants.len() as f32;
+        }
+        stats
+    }
+
+    // all plants of the habitat, including airborne and grounded seeds
+    pub fn get_plants(&self) -> Vec<PlantInfo> {
+        self.iter_all_plants().map(|(plant, state)| plant.get_info(state)).collect()
+    }
+
+    pub fn get_plant(&self, id: u64) -> Option<PlantInfo> {
+        self.iter_all_plants()
+            .find(|(plant, _)| plant.get_lineage().id == id)
+            .map(|(plant, state)| plant.get_info(state))
+    }
+
+    // the decoded genome of a living plant or seed, None if no plant with this id exists
+    pub fn get_genome_report(&self, id: u64) -> Option<GenomeReport> {
+        self.iter_all_plants()
+            .find(|(plant, _)| plant.get_lineage().id == id)
+            .map(|(plant, _)| plant.get_genome_report(&self.config))
+    }
+
+    // all currently existing children of a plant, the parent itself doesn't have to exist anymore
+    pub fn get_children(&self, parent_id: u64) -> Vec<PlantInfo> {
+        self.iter_all_plants()
+            .filter(|(plant, _)| plant.get_lineage().parent_id == Some(parent_id))
+            .map(|(plant, state)| plant.get_info(state))
+            .collect()
+    }
+
+    // the species found at the last clustering, see SimulationConfig::species_interval
+    pub fn get_species(&self) -> Vec<SpeciesInfo> {
+        self.species.get_species()
+    }
+
+    // coding map, mutations and fixation of every genome position across the living plants
+    pub fn genome_stats(&self) -> GenomeStats {
+        let genomes: Vec<&Genome> = self.plants.iter().map(|plant| plant.get_genome()).collect();
+        GenomeStats::new(&genomes, &self.mutation_counts)
+    }
+
+    // the ancestry of all plants which ever existed in the habitat
+    pub fn get_phylogeny(&self) -> &Phylogeny {
+        &self.phylogeny
+    }
+
+    // forgets all dead plants without living descendants, keeps the phylogeny small in long runs
+    pub fn prune_phylogeny(&mut self) {
+        self.phylogeny.prune_extinct();
+    }
+
+    pub fn get_selected_plant(&self) -> Option<PlantInfo> {
+        self.selected_plant_ix.map(|ix| self.plants[ix].get_info(PlantState::Living))
+    }
+
+    fn iter_all_plants(&self) -> impl Iterator<Item = (&Plant, PlantState)> {
+        self.plants.iter().map(|plant| (plant, PlantState::Living))
+            .chain(self.seeds.iter().map(|plant| (plant, PlantState::Airborne)))
+            .chain(self.ground_buffer.iter().flatten().map(|plant| (plant, PlantState::Grounded)))
+    }
+
     pub fn get_focus_information(&self) -> Option<HashMap<String, String>> {
         if let None = self.selected_pos {
             return None;
@@ -173,8 +569,20 @@ impl Habitat {
         let mut information: HashMap<String, String> = HashMap::new();
         if let Some(selected_plant_ix) = self.selected_plant_ix {
             let plant = &self.plants[selected_plant_ix];
+            let lineage = plant.get_lineage();
+            information.insert("id".into(), lineage.id.to_string());
+            if let Some(parent_id) = lineage.parent_id {
+                information.insert("parent_id".into(), parent_id.to_string());
+            }
+            if let Some(partner_id) = lineage.partner_id {
+                information.insert("partner_id".into(), partner_id.to_string());
+            }
+            information.insert("generation".into(), lineage.generation.to_string());
+            information.insert("birth_tick".into(), lineage.birth_tick.to_string());
             information.insert("energy".into(), plant.get_energy().to_string());
             information.insert("cell_count".into(), plant.get_cells().len().to_string());
+            information.insert("genome_length".into(), plant.get_genome().len().to_string());
+            information.insert("wing_size".into(), format!("{:.3}", plant.get_wing_size()));
         }
         Some(information)
     }
@@ -184,9 +592,41 @@ impl Habitat {
         self.selected_pos = Some(pos);
     }
 
+    // chooses a random living plant near the dying plant to pollinate one of its seeds,
+    // returns its id and a copy of its genome
+    fn find_pollinator(&mut self, mother_ix: usize) -> Option<(u64, Genome)> {
+        let mother_x = self.plants[mother_ix].get_pos().x;
+        let candidates: Vec<usize> = (0..self.plants.len())
+            .filter(|ix| *ix != mother_ix && !self.plants[*ix].is_dead(&self.config))
+            .filter(|ix| {
+                // distance on the horizontally wrapped grid
+                let distance = (self.plants[*ix].get_pos().x - mother_x).rem_euclid(self.grid_size.x);
+                distance.min(self.grid_size.x - distance) <= self.config.pollination_range
+            })
+            .collect();
+        if candidates.is_empty() {
+            return None;
+        }
+        let rng = self.rng.stage(RngStage::Pollination);
+        if !rng.random_bool(self.config.pollination_rate as f64) {
+            return None;
+        }
+        let partner = &self.plants[candidates[rng.random_range(0..candidates.len())]];
+        Some((partner.get_lineage().id, partner.get_genome().clone()))
+    }
+
+    // sorts all living plants into species, seeds keep the species of their parent
+    fn update_species(&mut self) {
+        let genomes: Vec<_> = self.plants.iter().map(|plant| plant.get_genome()).collect();
+        let assignments = self.species.cluster(&genomes, self.config.species_threshold, self.tick);
+        for (plant, species_id) in self.plants.iter_mut().zip(assignments) {
+            plant.set_species_id(species_id);
+        }
+    }
+
     fn apply_plants(&mut self) {
         // reinitialize whole grid as empty
-        self.cell_map = vec![vec![Empty; self.grid_size.y as usize]; self.grid_size.x as usize];
+        self.cell_map = vec![vec![Empty; self.get_map_size().y as usize]; self.grid_size.x as usize];
 
         // collecting leaves and trunks separately because leaves have priority
         let mut leaves = Vec::<(IVec2, CellType)>::new();
@@ -196,7 +636,7 @@ impl Habitat {
             for (pos, cell) in plant.get_cells().iter() {
                 match cell {
                     Leaf { .. } => {leaves.push((*pos, *cell));},
-                    Trunk { .. } => {trunks.push((*pos, *cell));},
+                    Trunk { .. } | Root { .. } => {trunks.push((*pos, *cell));},
                     _ => {}
                 }
             }
@@ -224,8 +664,25 @@ impl Habitat {
     fn is_in_grid(&self, pos: IVec2) -> bool {
         !(pos.x < 0 || pos.x >= self.grid_size.x || pos.y < 0 || pos.y >= self.grid_size.y)
     }
+    // roots can grow into the soil rows below the ground, without soil rows the terrain is solid
     fn is_in_y_bounds(&self, pos: IVec2) -> bool {
-        !(pos.y < 0 || pos.y >= self.grid_size.y)
+        let ground = self.ground_height[pos.x.rem_euclid(self.grid_size.x) as usize];
+        !(pos.y < -self.config.soil_depth || pos.y >= self.grid_size.y
+            || (self.config.soil_depth == 0 && pos.y < ground))
+    }
+
+    // the column and the index of the soil cell at a position below the ground
+    fn soil_index(ground_height: &[i32], pos: IVec2) -> (usize, usize) {
+        let x = pos.x.rem_euclid(ground_height.len() as i32) as usize;
+        (x, (ground_height[x] - 1 - pos.y) as usize)
+    }
+
+    // leaves and trunks need nutrients to grow if there is soil, roots only need energy
+    fn get_nutrient_cost(&self, cell_type: CellType) -> f32 {
+        match cell_type {
+            Leaf { .. } | Trunk { .. } if self.config.soil_depth > 0 => self.config.cell_nutrient_cost,
+            _ => 0.,
+        }
     }
 
     fn get_cell_at(&self, pos: IVec2) -> &CellType {
@@ -237,9 +694,9 @@ impl Habitat {
 
 
     fn spawn_random_cell(&mut self) {
-        let mut rng = rand::rng();
         // try to place a cell at random position, 100 tries at max
         for _ in 0..100 {
+            let rng = self.rng.stage(RngStage::Spawn);
             let pos = IVec2::new(
                 rng.random_range(0..self.grid_size.x),
                 rng.random_range(0..self.grid_size.y),
@@ -251,35 +708,92 @@ impl Habitat {
         }
     }
 
-    fn column_update(column: &mut Vec<CellType>) {
-        let mut current_energy = 1.;
-        for cell in column {
-            match cell {
-                Empty | CellType::Seed => {}
-                Leaf { sun_absorbed } => {
-                    *sun_absorbed = current_energy * LEAF_ABSORB_RATE;
-                    current_energy -= *sun_absorbed;
-                }
-                Trunk { .. } | CellType::Dead => {
-                    current_energy -= TRUNK_ABSORB_RATE * current_energy;
-                }
+    // the current season from -1 (midwinter) to 1 (midsummer), the year starts in spring,
+    // without seasons it is always 0
+    pub fn get_season(&self) -> f32 {
+        if self.config.year_length == 0 {
+            return 0.;
+        }
+        let time_of_year = (self.tick % self.config.year_length) as f32 / self.config.year_length as f32;
+        (time_of_year * std::f32::consts::TAU).sin()
+    }
 
+    // the angle of the sun rays in degrees at the current tick, None at night
+    pub fn get_sun_angle(&self) -> Option<f32> {
+        if self.config.day_length == 0 {
+            return Some(self.config.sun_angle);
+        }
+        // the day starts at sunrise and ends with the night
+        let time_of_day = (self.tick % self.config.day_length) as f32 / self.config.day_length as f32;
+        let daylight = 1. - self.config.night_fraction;
+        if time_of_day >= daylight {
+            return None;
+        }
+        // the sun sweeps from one side at sunrise to the other side at sunset
+        let progress = time_of_day / daylight;
+        Some(self.config.sun_angle + self.config.sun_angle_sweep * (2. * progress - 1.))
+    }
+
+    // a ray of light loses energy on every cell it passes, leaves store how much they absorbed
+    fn absorb_light(cell: &mut CellType, light: &mut f32, config: &SimulationConfig) {
+        match cell {
+            Empty | CellType::Seed | Root { .. } => {}
+            Leaf { sun_absorbed, exposure } => {
+                *exposure = *light;
+                *sun_absorbed = *light * config.leaf_absorb_rate;
+                *light -= *sun_absorbed;
+            }
+            Trunk { .. } | CellType::Dead => {
+                *light -= config.trunk_absorb_rate * *light;
             }
         }
     }
 
+    fn column_update(column: &mut [CellType], light: f32, config: &SimulationConfig) {
+        let mut current_energy = light;
+        for cell in column {
+            Habitat::absorb_light(cell, &mut current_energy, config);
+        }
+    }
+
     fn update_all_columns(&mut self) {
-        self.cell_map
-            .par_iter_mut()
-            .for_each(|column| Habitat::column_update(column))
+        let config = &self.config;
+        let (light, angle) = match self.get_sun_angle() {
+            Some(angle) => (1., angle),
+            None => (0., 0.),
+        };
+        let slope = angle.to_radians().tan();
+        // light only reaches the cells above the ground
+        if slope == 0. {
+            self.cell_map
+                .par_iter_mut()
+                .zip(self.ground_height.par_iter())
+                .for_each(|(column, ground)| Habitat::column_update(&mut column[..(self.grid_size.y - ground) as usize], light, config));
+            return;
+        }
+        // diagonal rays start in every column at the top and wrap around the grid,
+        // in each row all rays are shifted by the same amount, so every cell is hit by exactly one ray
+        let width = self.grid_size.x;
+        let mut rays = vec![light; width as usize];
+        for row in 0..self.grid_size.y as usize {
+            let shift = (row as f32 * slope).floor() as i32;
+            for (ray_x, ray) in rays.iter_mut().enumerate() {
+                let x = (ray_x as i32 + shift).rem_euclid(width) as usize;
+                // rays which hit the terrain are blocked, so hills cast shadows
+                if row as i32 >= self.grid_size.y - self.ground_height[x] {
+                    *ray = 0.;
+                }
+                Habitat::absorb_light(&mut self.cell_map[x][row], ray, config);
+            }
+        }
     }
 
-    fn create_growths(&self) -> Vec<Vec<(IVec2, usize, CellType, f32)>> {
+    fn create_growths(&mut self) -> Vec<Vec<(IVec2, usize, CellType, f32)>> {
         // grow all plants
         let mut plant_growth: Vec<Vec<(IVec2, usize, CellType, f32)>> = Vec::with_capacity(self.plants.len());
 
         for plant in &self.plants {
-            let mut growth_proposals = plant.create_growth_proposals();
+            let mut growth_proposals = plant.create_growth_proposals(&self.config, &self.ground_height);
             growth_proposals.retain(|(pos, _, _, _)|
                 // check if growth doesn't leave y bounds
                 self.is_in_y_bounds(*pos)
@@ -289,13 +803,19 @@ impl Habitat {
                     _ => false
                 }
             );
-            let mut growths = Vec::with_capacity(MAX_GROWTHS_PER_ITERATION);
+            let mut growths = Vec::with_capacity(self.config.max_growths_per_iteration);
+            let mut nutrients = plant.get_nutrients();
 
-            for _ in 0..MAX_GROWTHS_PER_ITERATION {
+            for _ in 0..self.config.max_growths_per_iteration {
+                // only proposals the plant has enough nutrients for are possible
+                growth_proposals.retain(|(_, _, cell_type, _)| self.get_nutrient_cost(*cell_type) <= nutrients);
                 // if there are still growth proposals left...
                 if growth_proposals.len() > 0 {
                     // ...add a random proposal to the growths, while removing it from the choice_pool
-                    growths.push(growth_proposals.remove(gen_range(0, growth_proposals.len())));
+                    let choice = self.rng.stage(RngStage::Growth).random_range(0..growth_proposals.len());
+                    let growth = growth_proposals.remove(choice);
+                    nutrients -= self.get_nutrient_cost(growth.2);
+                    growths.push(growth);
                 }
             }
             // add the vector of growths for the plant
@@ -305,6 +825,140 @@ impl Habitat {
     }
 
 
+    fn supply_plant_nutrients(&mut self) {
+        if self.config.soil_depth == 0 {
+            return;
+        }
+        // every soil cell holds at most one root, so the order of the plants doesn't matter
+        for plant in &mut self.plants {
+            let mut drawn = 0.;
+            for (pos, cell) in plant.get_cells() {
+                if let Root { .. } = cell {
+                    let (x, ix) = Habitat::soil_index(&self.ground_height, pos);
+                    let nutrients = &mut self.soil[x][ix];
+                    let uptake = nutrients.min(self.config.root_uptake_rate);
+                    *nutrients -= uptake;
+                    drawn += uptake;
+                }
+            }
+            plant.give_nutrients(drawn);
+        }
+        let soil_capacity = self.config.soil_capacity;
+        let soil_regeneration = self.config.soil_regeneration;
+        // soil enriched by litter can hold more than the capacity, it only doesn't regenerate beyond it
+        for nutrients in self.soil.iter_mut().flatten() {
+            if *nutrients < soil_capacity {
+                *nutrients = (*nutrients + soil_regeneration).min(soil_capacity);
+            }
+        }
+    }
+
+    // decayed cells above the ground become litter in their column, decayed roots enrich their soil cell directly,
+    // the litter seeps into the top soil row for the roots and feeds the seeds waiting in the column
+    fn recycle_nutrients(&mut self, decayed_cells: Vec<IVec2>) {
+        if self.config.soil_depth == 0 {
+            return;
+        }
+        for pos in decayed_cells {
+            let x = pos.x.rem_euclid(self.grid_size.x) as usize;
+            if pos.y < self.ground_height[x] {
+                let (x, ix) = Habitat::soil_index(&self.ground_height, pos);
+                self.soil[x][ix] += self.config.decay_nutrients;
+            } else {
+                self.litter[x] += self.config.decay_nutrients;
+            }
+        }
+        for ((litter, soil), seeds) in self.litter.iter_mut().zip(&mut self.soil).zip(&mut self.ground_buffer) {
+            for seed in seeds {
+                let uptake = litter.min(self.config.seed_litter_uptake);
+                *litter -= uptake;
+                seed.give_nutrients(uptake);
+            }
+            let seeping = *litter * self.config.litter_seep_rate;
+            *litter -= seeping;
+            soil[0] += seeping;
+        }
+    }
+
+    fn update_water(&mut self) {
+        if self.config.soil_depth == 0 {
+            return;
+        }
+        let config = &self.config;
+        self.raining = self.rng.stage(RngStage::Weather).random_bool(config.rain_rate as f64);
+        for (surface, column) in self.surface_water.iter_mut().zip(&mut self.moisture) {
+            if self.raining {
+                *surface += config.rain_amount;
+            }
+            // the water on the ground seeps into the top soil row as far as it has room
+            let infiltration = surface.min(config.infiltration_rate).min(config.moisture_capacity - column[0]).max(0.);
+            *surface -= infiltration;
+            column[0] += infiltration;
+            *surface *= 1. - config.evaporation_rate;
+            column[0] *= 1. - config.evaporation_rate;
+        }
+
+        // every cell exchanges a share of the difference with its neighbors, columns wrap around horizontally,
+        // the horizontal neighbors are the cells at the same height, so water doesn't flow out of a cliff
+        let width = self.moisture.len();
+        let ground_height = &self.ground_height;
+        let old = self.moisture.clone();
+        for x in 0..width {
+            for ix in 0..old[x].len() {
+                let y = ground_height[x] - 1 - ix as i32;
+                let mut neighbors = Vec::with_capacity(4);
+                for neighbor_x in [(x + 1) % width, (x + width - 1) % width] {
+                    let neighbor_ix = ground_height[neighbor_x] - 1 - y;
+                    if neighbor_ix >= 0 && (neighbor_ix as usize) < old[neighbor_x].len() {
+                        neighbors.push(old[neighbor_x][neighbor_ix as usize]);
+                    }
+                }
+                if ix > 0 {
+                    neighbors.push(old[x][ix - 1]);
+                }
+                if ix + 1 < old[x].len() {
+                    neighbors.push(old[x][ix + 1]);
+                }
+                let flow: f32 = neighbors.iter().map(|neighbor| neighbor - old[x][ix]).sum();
+                self.moisture[x][ix] = (old[x][ix] + config.moisture_diffusion * flow).min(config.moisture_capacity);
+            }
+        }
+    }
+
+    // every leaf needs water, roots draw it from their soil cells until the demand is met,
+    // the missing share costs energy and lets leaves dry out
+    fn supply_plant_water(&mut self) {
+        if self.config.soil_depth == 0 || self.config.leaf_water_demand == 0. {
+            return;
+        }
+        let config = &self.config;
+        let rng = self.rng.stage(RngStage::Weather);
+        for plant in &mut self.plants {
+            let cells = plant.get_cells();
+            let demand = cells.iter().filter(|(_, cell)| matches!(cell, Leaf { .. })).count() as f32 * config.leaf_water_demand;
+            let mut supply = 0.;
+            for (pos, cell) in cells {
+                if supply >= demand {
+                    break;
+                }
+                if let Root { .. } = cell {
+                    let (x, ix) = Habitat::soil_index(&self.ground_height, pos);
+                    let water = &mut self.moisture[x][ix];
+                    let uptake = water.min(config.root_water_uptake).min(demand - supply);
+                    *water -= uptake;
+                    supply += uptake;
+                }
+            }
+            if supply < demand {
+                self.drought_plants += 1;
+                let deficit = 1. - supply / demand;
+                plant.give_energy(-(demand - supply) * config.drought_energy_cost);
+                let leaf_loss = (config.drought_leaf_loss * deficit) as f64;
+                self.dead_cells.extend(plant.remove_leaves(|| rng.random_bool(leaf_loss)));
+            }
+        }
+    }
+
     fn supply_plant_energy(&mut self) {
         let mut plant_leaves = Vec::<Vec<IVec2>>::new();
 
@@ -324,17 +978,22 @@ impl Habitat {
         }
 
         // going over the collected leaves of each plant and calculating the energy
+        let season = self.get_season();
+        let sun_power = self.config.sun_power * (1. + self.config.season_sun_amplitude * season);
+        let sustain_cost = self.config.cell_sustain_energy_cost * (1. - self.config.season_sustain_amplitude * season);
+        self.absorbed_sunlight = 0.;
         for (idx, leaves) in plant_leaves.iter().enumerate() {
             let mut collected_energy = 0f32;
             for leaf_pos in leaves {
                 match self.get_cell_at(*leaf_pos) {
-                    Leaf { sun_absorbed } => {
-                        collected_energy += sun_absorbed * SUN_POWER;
+                    Leaf { sun_absorbed, .. } => {
+                        collected_energy += sun_absorbed * sun_power;
                     }
                     _ => {}
                 }
             }
-            let energy_cost = self.plants[idx].get_cells().len() as f32 * CELL_SUSTAIN_ENERGY_COST;
+            self.absorbed_sunlight += collected_energy;
+            let energy_cost = self.plants[idx].get_cells().len() as f32 * sustain_cost;
             self.plants[idx].give_energy(
                 collected_energy - energy_cost);
         }
@@ -343,17 +1002,34 @@ impl Habitat {
     fn update_seeds(&mut self) {
         // iterating over all seed indexes
         let mut indexes = Vec::<(usize, i32)>::with_capacity(self.plants.len());
+        // the gust of this update changes the wind for all seeds alike
+        self.wind = self.config.wind_speed;
+        if self.config.wind_gust_strength > 0. {
+            let gust_strength = self.config.wind_gust_strength;
+            self.wind += self.rng.stage(RngStage::Wind).random_range(-gust_strength..=gust_strength);
+        }
         for ix in 0..self.seeds.len() {
             // updating the position to let the seed drop to 1 of the 3 lower cells
             let (x, y) = self.seeds[ix].get_pos().into();
-            let new_pos = IVec2::new(x + gen_range(-1, 2), y-1);
+            let mut x_offset = self.rng.stage(RngStage::Seeds).random_range(-1..2);
+            // the wind pushes the seed sideways, a fractional push moves it by one cell with the matching chance
+            let push = self.wind * (1. - self.config.wind_shear * (1. - y as f32 / (self.grid_size.y - 1).max(1) as f32));
+            x_offset += push.floor() as i32;
+            if push.fract() != 0. {
+                x_offset += self.rng.stage(RngStage::Wind).random_bool((push - push.floor()) as f64) as i32;
+            }
+            // seeds with wings glide and don't fall in every update
+            let wing_glide = self.config.wing_glide * self.seeds[ix].get_wing_size();
+            let falls = wing_glide == 0. || self.rng.stage(RngStage::Wind).random_bool(1. / (1. + wing_glide as f64));
+            let new_pos = IVec2::new((x + x_offset).rem_euclid(self.grid_size.x), y - falls as i32);
             self.seeds[ix].set_pos(new_pos);
             // if the seed is now below ground it can potentially become a tree
-            if let IVec2 { x, y: -1 } = self.seeds[ix].get_pos() {
+            let ground = self.ground_height[new_pos.x as usize];
+            if new_pos.y < ground {
                 // setting seed position to ground level so it can grow a root
-                self.seeds[ix].set_pos(IVec2::new(x + 1, 0));
+                self.seeds[ix].set_pos(IVec2::new(new_pos.x, ground));
                 // saving seed index, to not mess up vector length
-                indexes.push((ix, x));
+                indexes.push((ix, new_pos.x));
             }
         }
         indexes.reverse();
@@ -373,21 +1049,25 @@ impl Habitat {
     }
 
     fn update_grounded_buffer(&mut self) {
+        // seeds stay dormant until the season is warm enough, but keep loosing energy
+        let germinating = self.get_season() >= self.config.germination_threshold;
         let mut growing_seeds = Vec::<(usize, usize)>::with_capacity(self.grid_size.x as usize);
-        for (ix, container ) in self.ground_buffer.iter().enumerate() {
-            // check if the ground tile at that position is free
-            if let Empty = self.get_cell_at((ix as i32, 0i32).into()) {
-                match container.len() {
-                    0 => {}
-                    1 => {
-                        growing_seeds.push((ix, 0));
-                    }
-                    _ => {
-                        // the plant with the highest amount of energy is allowed to grow
-                        let container_ix = container.into_iter().enumerate()
-                            .max_by(|(_, plant1), (_, plant2) |
-                                plant1.get_energy().total_cmp(&plant2.get_energy())).unwrap().0;
-                        growing_seeds.push((ix, container_ix));
+        if germinating {
+            for (ix, container ) in self.ground_buffer.iter().enumerate() {
+                // check if the ground tile at that position is free
+                if let Empty = self.get_cell_at((ix as i32, self.ground_height[ix]).into()) {
+                    match container.len() {
+                        0 => {}
+                        1 => {
+                            growing_seeds.push((ix, 0));
+                        }
+                        _ => {
+                            // the plant with the highest amount of energy is allowed to grow
+                            let container_ix = container.into_iter().enumerate()
+                                .max_by(|(_, plant1), (_, plant2) |
+                                    plant1.get_energy().total_cmp(&plant2.get_energy())).unwrap().0;
+                            growing_seeds.push((ix, container_ix));
+                        }
                     }
                 }
             }
@@ -400,17 +1080,83 @@ impl Habitat {
             plant.create_root();
             // add the cell to plants
             self.plants.push(plant);
+            self.births += 1;
         }
 
 
         // reduce energy for remaining seeds
+        // with seasons seeds which didn't root stay dormant until their energy runs out,
+        // without seasons they die right away
+        let dormant = self.config.year_length > 0;
+        let config = &self.config;
+        let is_gone = |seed: &Plant| if dormant { seed.get_energy() <= 0. } else { seed.is_dead(config) };
         for ix in 0..self.grid_size.x as usize {
             for plant in &mut self.ground_buffer[ix] {
-                plant.give_energy(-SEED_ENERGY_DRAIN)
+                plant.give_energy(-config.seed_energy_drain)
+            }
+            // remove all seeds which are gone
+            for seed in self.ground_buffer[ix].iter().filter(|seed| is_gone(seed)) {
+                self.phylogeny.record_death(seed.get_lineage().id, self.tick);
             }
-            // remove all seeds which now have no energy
-            self.ground_buffer[ix].retain(|seed| !seed.is_dead())
+            self.ground_buffer[ix].retain(|seed| !is_gone(seed))
         }
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sim_mod::genome::Crossover;
+
+    // a small habitat which uses every random stage of the update
+    fn test_config() -> SimulationConfig {
+        SimulationConfig {
+            crossover: Crossover::Uniform,
+            duplication_rate: 0.05,
+            deletion_rate: 0.05,
+            creep_rate: 0.01,
+            day_length: 40,
+            year_length: 200,
+            winter_leaf_loss: 0.05,
+            trunk_load_capacity: 20.,
+            terrain_height: 4,
+            soil_depth: 4,
+            rain_rate: 0.2,
+            wind_speed: 0.5,
+            wind_gust_strength: 0.5,
+            wing_glide: 1.,
+            species_interval: 20,
+            ..SimulationConfig::with_grid_size(IVec2::new(48, 20))
+        }
+    }
+
+    // the serialized cell map and stats after every update
+    fn run(config: SimulationConfig, seed: u64, ticks: u64) -> Vec<(String, String)> {
+        let mut habitat = Habitat::with_seed(config, seed).unwrap();
+        habitat.set_minimum_plants(10);
+        (0..ticks)
+            .map(|_| {
+                habitat.update();
+                (serde_json::to_string(habitat.get_cell_map()).unwrap(), serde_json::to_string(&habitat.stats()).unwrap())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn same_seed_gives_same_run() {
+        let first = run(test_config(), 7, 300);
+        assert!(first.iter().any(|(_, stats)| serde_json::from_str::<HabitatStats>(stats).unwrap().living_plants > 0));
+        assert_eq!(first, run(test_config(), 7, 300));
+        assert_ne!(first, run(test_config(), 8, 300));
+    }
+
+    #[test]
+    fn thread_count_does_not_change_run() {
+        let run_with_threads = |threads| {
+            rayon::ThreadPoolBuilder::new().num_threads(threads).build().unwrap()
+                .install(|| run(test_config(), 7, 300))
+        };
+        assert_eq!(run_with_threads(1), run_with_threads(4));
+    }
+}
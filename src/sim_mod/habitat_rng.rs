@@ -0,0 +1,48 @@
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+
+// every stage of the habitat update that needs randomness draws from its own stream,
+// so adding or removing random calls in one stage doesn't change the outcome of the others
+#[derive(Copy, Clone)]
+pub enum RngStage {
+    Spawn,      // placing new random seeds
+    Offspring,  // deciding which dead cells become seeds and seeding the plant rngs
+    Growth,     // choosing between growth proposals
+    Decay,      // removal of dead cells
+    Seeds,      // movement of airborne seeds
+    Pollination, // choosing a partner for new seeds
+    Season,     // leaves dying in winter
+    Weather,    // rain and leaves dying of drought
+    Wind,       // gusts and the drift and gliding of airborne seeds
+    Terrain,    // generating the ground height when the habitat is created
+}
+
+const STAGE_COUNT: u64 = 10;
+
+#[derive(Serialize, Deserialize)]
+pub struct HabitatRng {
+    seed: u64,
+    streams: Vec<ChaCha8Rng>,
+}
+
+impl HabitatRng {
+    pub fn new(seed: u64) -> Self {
+        let streams = (0..STAGE_COUNT)
+            .map(|stream| {
+                let mut rng = ChaCha8Rng::seed_from_u64(seed);
+                rng.set_stream(stream);
+                rng
+            })
+            .collect();
+        Self { seed, streams }
+    }
+
+    pub fn get_seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn stage(&mut self, stage: RngStage) -> &mut ChaCha8Rng {
+        &mut self.streams[stage as usize]
+    }
+}
@@ -0,0 +1,31 @@
+use serde::{Deserialize, Serialize};
+
+// population statistics of a habitat after an update, see Habitat::stats
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct HabitatStats {
+    pub tick: u64,
+    pub living_plants: usize,
+    pub airborne_seeds: usize,
+    pub grounded_seeds: usize,
+    pub grounded_seeds_per_column: Vec<usize>,
+    pub total_energy: f32,          // energy values only count living plants
+    pub mean_energy: f32,
+    pub max_energy: f32,
+    pub mean_plant_height: f32,
+    pub species: usize,             // the amount of species at the last clustering
+    pub leaf_cells: usize,
+    pub trunk_cells: usize,
+    pub root_cells: usize,
+    pub absorbed_sunlight: f32,     // the energy all leaves gathered in the last update
+    pub births: usize,              // seeds which grew a root in the last update
+    pub deaths: usize,              // plants which died in the last update
+    pub season: f32,                // from -1 (midwinter) to 1 (midsummer), 0 without seasons
+    pub broken_cells: usize,        // cells which broke off overloaded branches in the last update
+    pub soil_nutrients: f32,        // the nutrients of all soil cells together
+    pub litter_nutrients: f32,      // the nutrients of decayed cells lying on the ground
+    pub soil_moisture: f32,         // the water of all soil cells together
+    pub raining: bool,              // whether it rained in the last update
+    pub drought_plants: usize,      // plants which got less water than their leaves need in the last update
+    pub wind: f32,                  // the wind speed at the top of the grid in the last update, including the gust
+    pub mean_wing_size: f32,        // the mean wing size of the living plants, from 0 to 1
+}
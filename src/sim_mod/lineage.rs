@@ -0,0 +1,33 @@
+use serde::{Deserialize, Serialize};
+
+// the identity of a plant and its place in the family tree, stays the same for the whole life of a plant
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Lineage {
+    pub id: u64,                // unique in the habitat, ids are never reused
+    pub parent_id: Option<u64>, // randomly spawned plants have no parent
+    pub partner_id: Option<u64>, // the plant which pollinated the seed, None for asexual offspring
+    pub generation: u32,        // randomly spawned plants are generation 0
+    pub birth_tick: u64,        // the tick at which the seed was created
+}
+
+impl Lineage {
+    pub fn new_origin(id: u64, birth_tick: u64) -> Self {
+        Self {
+            id,
+            parent_id: None,
+            partner_id: None,
+            generation: 0,
+            birth_tick,
+        }
+    }
+
+    pub fn new_child(&self, id: u64, birth_tick: u64, partner_id: Option<u64>) -> Self {
+        Self {
+            id,
+            parent_id: Some(self.id),
+            partner_id,
+            generation: self.generation + 1,
+            birth_tick,
+        }
+    }
+}
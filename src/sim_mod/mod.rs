@@ -1,7 +1,20 @@
 pub mod cell_types;
 pub mod habitat;
+pub mod habitat_stats;
+mod habitat_rng;
+pub mod simulation_config;
+pub mod snapshot;
+pub mod stats_recorder;
 mod plant;
-mod genome;
+pub mod plant_info;
+pub mod lineage;
+pub mod phylogeny;
+pub mod species;
+pub mod genome;
+pub mod genome_report;
+pub mod genome_stats;
+pub mod plant_program;
 mod response_cluster;
 mod response;
-mod plant_cell;
\ No newline at end of file
+mod plant_cell;
+mod terrain;
\ No newline at end of file
@@ -0,0 +1,234 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+use serde::{Deserialize, Serialize};
+use crate::sim_mod::lineage::Lineage;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PhylogenyNode {
+    pub lineage: Lineage,
+    pub death_tick: Option<u64>,    // None while the plant (or seed) is still alive
+}
+
+// the ancestry of every plant which ever existed in a habitat, dead plants are kept until they get pruned
+#[derive(Default, Serialize, Deserialize)]
+pub struct Phylogeny {
+    nodes: BTreeMap<u64, PhylogenyNode>,
+}
+
+impl Phylogeny {
+    pub fn record_birth(&mut self, lineage: Lineage) {
+        self.nodes.insert(lineage.id, PhylogenyNode { lineage, death_tick: None });
+    }
+
+    pub fn record_death(&mut self, id: u64, tick: u64) {
+        if let Some(node) = self.nodes.get_mut(&id) {
+            node.death_tick = Some(tick);
+        }
+    }
+
+    pub fn get_node(&self, id: u64) -> Option<&PhylogenyNode> {
+        self.nodes.get(&id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    // removes all branches which have no living plant (or seed) in them anymore
+    pub fn prune_extinct(&mut self) {
+        let keep = self.get_surviving_ids();
+        self.nodes.retain(|id, _| keep.contains(id));
+    }
+
+    // the ids of all living nodes and all of their ancestors
+    fn get_surviving_ids(&self) -> BTreeSet<u64> {
+        let mut keep = BTreeSet::new();
+        for node in self.nodes.values().filter(|node| node.death_tick.is_none()) {
+            let mut current = Some(node.lineage.id);
+            // walk up until the root or an ancestor which is already kept
+            while let Some(id) = current {
+                if !keep.insert(id) {
+                    break;
+                }
+                current = self.nodes.get(&id).and_then(|node| node.lineage.parent_id);
+            }
+        }
+        keep
+    }
+
+    // the children ids of every node and the ids of all roots, optionally without the extinct branches
+    // nodes whose parent got pruned are treated as roots
+    fn get_tree(&self, prune_extinct: bool) -> (Vec<u64>, BTreeMap<u64, Vec<u64>>) {
+        let keep = prune_extinct.then(|| self.get_surviving_ids());
+        let mut roots = Vec::new();
+        let mut children = BTreeMap::<u64, Vec<u64>>::new();
+        for (id, node) in &self.nodes {
+            if keep.as_ref().is_some_and(|keep| !keep.contains(id)) {
+                continue;
+            }
+            match node.lineage.parent_id {
+                Some(parent_id) if self.nodes.contains_key(&parent_id) =>
+                    children.entry(parent_id).or_default().push(*id),
+                _ => roots.push(*id),
+            }
+        }
+        (roots, children)
+    }
+
+    // the length of the branch to a node is the time between the births of the parent and the node,
+    // all roots are joined by an unnamed node, because newick can only describe a single tree
+    pub fn to_newick(&self, prune_extinct: bool) -> String {
+        let (roots, children) = self.get_tree(prune_extinct);
+        let mut out = String::from("(");
+
+        // iterative depth first traversal, so deep lineages can't overflow the stack
+        // (id, visited) -> a visited node has already written its children
+        let mut stack: Vec<(u64, bool)> = roots.iter().rev().map(|id| (*id, false)).collect();
+        let mut first_in_level = true;
+        while let Some((id, visited)) = stack.pop() {
+            let node = &self.nodes[&id];
+            if visited {
+                out.push(')');
+                self.write_newick_label(&mut out, node);
+                first_in_level = false;
+                continue;
+            }
+            if !first_in_level {
+                out.push(',');
+            }
+            match children.get(&id) {
+                Some(node_children) => {
+                    out.push('(');
+                    stack.push((id, true));
+                    stack.extend(node_children.iter().rev().map(|id| (*id, false)));
+                    first_in_level = true;
+                }
+                None => {
+                    self.write_newick_label(&mut out, node);
+                    first_in_level = false;
+                }
+            }
+        }
+        out.push_str(");");
+        out
+    }
+
+    fn write_newick_label(&self, out: &mut String, node: &PhylogenyNode) {
+        let branch_length = node.lineage.parent_id
+            .and_then(|parent_id| self.nodes.get(&parent_id))
+            .map(|parent| node.lineage.birth_tick - parent.lineage.birth_tick)
+            .unwrap_or(0);
+        write!(out, "{}:{}", node.lineage.id, branch_length).unwrap();
+    }
+
+    // a json array of all root nodes, every node holds its lineage, death tick and an array of its children
+    pub fn to_json(&self, prune_extinct: bool) -> String {
+        let (roots, children) = self.get_tree(prune_extinct);
+        let mut out = String::from("[");
+
+        // same traversal as in to_newick
+        let mut stack: Vec<(u64, bool)> = roots.iter().rev().map(|id| (*id, false)).collect();
+        let mut first_in_level = true;
+        while let Some((id, visited)) = stack.pop() {
+            if visited {
+                out.push_str("]}");
+                first_in_level = false;
+                continue;
+            }
+            if !first_in_level {
+                out.push(',');
+            }
+            let node = &self.nodes[&id];
+            write!(out, "{{\"id\":{},\"parent_id\":{},\"partner_id\":{},\"generation\":{},\"birth_tick\":{},\"death_tick\":{},\"children\":[",
+                   node.lineage.id,
+                   Self::json_option(node.lineage.parent_id),
+                   Self::json_option(node.lineage.partner_id),
+                   node.lineage.generation,
+                   node.lineage.birth_tick,
+                   Self::json_option(node.death_tick)).unwrap();
+            stack.push((id, true));
+            if let Some(node_children) = children.get(&id) {
+                stack.extend(node_children.iter().rev().map(|id| (*id, false)));
+            }
+            first_in_level = true;
+        }
+        out.push(']');
+        out
+    }
+
+    fn json_option(value: Option<u64>) -> String {
+        value.map_or("null".into(), |x| x.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 0 ─┬─ 1 ── 3 ── 6        5
+    //    └─ 2 ── 4
+    // only 3 and 5 are still alive
+    fn test_phylogeny() -> Phylogeny {
+        let mut phylogeny = Phylogeny::default();
+        let origin = Lineage::new_origin(0, 0);
+        let first = origin.new_child(1, 5, None);
+        let second = origin.new_child(2, 7, None);
+        let third = first.new_child(3, 12, Some(2));
+        for lineage in [origin, first, second, third, second.new_child(4, 9, None),
+                        Lineage::new_origin(5, 20), third.new_child(6, 15, None)] {
+            phylogeny.record_birth(lineage);
+        }
+        for (id, tick) in [(0, 10), (1, 30), (2, 25), (4, 14), (6, 16)] {
+            phylogeny.record_death(id, tick);
+        }
+        phylogeny
+    }
+
+    #[test]
+    fn newick_of_known_tree() {
+        let phylogeny = test_phylogeny();
+        assert_eq!(phylogeny.to_newick(false), "((((6:3)3:7)1:5,(4:2)2:7)0:0,5:0);");
+        assert_eq!(phylogeny.to_newick(true), "(((3:7)1:5)0:0,5:0);");
+        assert_eq!(Phylogeny::default().to_newick(false), "();");
+    }
+
+    #[test]
+    fn json_is_valid() {
+        let phylogeny = test_phylogeny();
+        let roots: serde_json::Value = serde_json::from_str(&phylogeny.to_json(false)).unwrap();
+        assert_eq!(roots.as_array().unwrap().len(), 2);
+        assert_eq!(roots[0]["death_tick"], 10);
+        assert_eq!(roots[0]["children"].as_array().unwrap().len(), 2);
+        let third = &roots[0]["children"][0]["children"][0];
+        assert_eq!(third["id"], 3);
+        assert_eq!(third["parent_id"], 1);
+        assert_eq!(third["partner_id"], 2);
+        assert_eq!(third["generation"], 2);
+        assert!(third["death_tick"].is_null());
+        assert_eq!(third["children"][0]["id"], 6);
+        assert_eq!(roots[1]["id"], 5);
+        assert!(roots[1]["children"].as_array().unwrap().is_empty());
+
+        let pruned: serde_json::Value = serde_json::from_str(&phylogeny.to_json(true)).unwrap();
+        assert_eq!(pruned[0]["children"].as_array().unwrap().len(), 1);
+        assert_eq!(serde_json::from_str::<serde_json::Value>(&Phylogeny::default().to_json(false)).unwrap(),
+                   serde_json::json!([]));
+    }
+
+    #[test]
+    fn pruning_keeps_ancestors_of_living_plants() {
+        let mut phylogeny = test_phylogeny();
+        phylogeny.prune_extinct();
+        assert_eq!(phylogeny.len(), 4);
+        for id in [0, 1, 3, 5] {
+            assert!(phylogeny.get_node(id).is_some(), "{id} got pruned");
+        }
+        for id in [2, 4, 6] {
+            assert!(phylogeny.get_node(id).is_none(), "{id} wasn't pruned");
+        }
+    }
+}
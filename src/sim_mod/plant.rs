@@ -1,67 +1,168 @@
-use crate::constants::simulation::{BASE_MAX_AGE, CELL_GROWTH_COST, DEFAULT_ENERGY, LIFETIME_FACTOR, MAX_AGE_CELL_MODIFIER, RESPONSE_SIZE, ROOT_CON_DECAY};
+use std::collections::HashMap;
 use crate::sim_mod::cell_types::CellType;
-use crate::sim_mod::cell_types::CellType::Trunk;
+use crate::sim_mod::cell_types::CellType::{Leaf, Root, Trunk};
 use crate::sim_mod::genome::Genome;
+use crate::sim_mod::genome_report::GenomeReport;
+use crate::sim_mod::lineage::Lineage;
 use crate::sim_mod::plant_cell::PlantCell;
+use crate::sim_mod::plant_info::{PlantInfo, PlantState};
 use crate::sim_mod::response_cluster::ResponseCluster;
-use macroquad::math::IVec2;
+use crate::sim_mod::simulation_config::SimulationConfig;
+use glam::IVec2;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
 
+#[derive(Serialize, Deserialize)]
 pub struct Plant {
+    lineage: Lineage,
+    species_id: Option<u64>,    // seeds carry the species of their parent until the next clustering
     cells: Vec<PlantCell>,
     pos: IVec2,
     has_root: bool,
     energy: f32,
+    nutrients: f32,     // drawn from the soil by root cells, growing leaves and trunks needs them
     lifetime: usize,
     genome: Genome,
     response_clusters: Vec<ResponseCluster>,
+    wing_size: f32,     // decoded after the response clusters, seeds with wings glide longer but have less energy
+    rng: ChaCha8Rng,    // each plant has its own random stream, used for its genome and offspring
 }
 
 impl Plant {
-    pub fn new(pos: IVec2) -> Self {
-        let mut genome = Genome::new();
-        let responses = Self::create_responses(&mut genome);
+    pub fn new(pos: IVec2, config: &SimulationConfig, rng_seed: u64, lineage: Lineage) -> Self {
+        let mut rng = ChaCha8Rng::seed_from_u64(rng_seed);
+        let mut genome = Genome::new(config, &mut rng);
+        let responses = Self::create_responses(&mut genome, config);
+        let wing_size = genome.parse_value_normalized();
 
         Self {
+            lineage,
+            species_id: None,
             cells: Vec::new(),
             pos,
             has_root: false,
-            energy: DEFAULT_ENERGY,
+            energy: config.default_energy - wing_size * config.wing_energy_cost,
+            nutrients: config.seed_nutrients,
             lifetime: 0,
             genome,
             response_clusters: responses,
+            wing_size,
+            rng,
         }
     }
 
-    pub fn new_with_root(root_pos: IVec2) -> Self {
-        let mut genome = Genome::new();
-        let responses = Self::create_responses(&mut genome);
+    pub fn new_with_root(root_pos: IVec2, config: &SimulationConfig, rng_seed: u64, lineage: Lineage) -> Self {
+        let mut rng = ChaCha8Rng::seed_from_u64(rng_seed);
+        let mut genome = Genome::new(config, &mut rng);
+        let responses = Self::create_responses(&mut genome, config);
+        let wing_size = genome.parse_value_normalized();
 
         Self {
+            lineage,
+            species_id: None,
             cells: vec![PlantCell::new_root(root_pos)],
             pos: root_pos,
             has_root: true,
-            energy: DEFAULT_ENERGY,
+            energy: config.default_energy,
+            nutrients: config.seed_nutrients,
             lifetime: 0,
             genome,
             response_clusters: responses,
+            wing_size,
+            rng,
         }
     }
 
-    pub fn new_offspring(&self, pos: IVec2) -> Self {
-        let mut genome = self.genome.create_offspring();
-        let responses = Self::create_responses(&mut genome);
+    // a seed with a given genome, the rng is only used for its offspring
+    pub fn new_with_genome(pos: IVec2, config: &SimulationConfig, rng_seed: u64, lineage: Lineage, mut genome: Genome) -> Self {
+        genome.rewind();
+        let responses = Self::create_responses(&mut genome, config);
+        let wing_size = genome.parse_value_normalized();
 
         Self {
+            lineage,
+            species_id: None,
+            cells: Vec::new(),
+            pos,
+            has_root: false,
+            energy: config.default_energy - wing_size * config.wing_energy_cost,
+            nutrients: config.seed_nutrients,
+            lifetime: 0,
+            genome,
+            response_clusters: responses,
+            wing_size,
+            rng: ChaCha8Rng::seed_from_u64(rng_seed),
+        }
+    }
+
+    // id and tick are the id of the new plant and the current tick of the habitat,
+    // a seed with a partner (id and genome of the pollinating plant) gets a recombined genome,
+    // mutations get counted in mutation_counts
+    pub fn new_offspring(&mut self, pos: IVec2, config: &SimulationConfig, id: u64, tick: u64,
+                         partner: Option<(u64, &Genome)>, mutation_counts: &mut Vec<u64>) -> Self {
+        let mut rng = ChaCha8Rng::seed_from_u64(self.rng.random());
+        let mut genome = match partner {
+            Some((_, partner_genome)) => self.genome
+                .crossover(partner_genome, config, &mut rng)
+                .create_offspring(config, &mut rng, mutation_counts),
+            None => self.genome.create_offspring(config, &mut rng, mutation_counts),
+        };
+        let responses = Self::create_responses(&mut genome, config);
+        let wing_size = genome.parse_value_normalized();
+
+        Self {
+            lineage: self.lineage.new_child(id, tick, partner.map(|(partner_id, _)| partner_id)),
+            species_id: self.species_id,
             cells: vec![],
             pos,
             has_root: false,
-            energy: DEFAULT_ENERGY + self.lifetime as f32 * LIFETIME_FACTOR,
+            energy: config.default_energy + self.lifetime as f32 * config.lifetime_factor
+                - wing_size * config.wing_energy_cost,
+            nutrients: config.seed_nutrients,
             lifetime: 0,
-            genome: self.genome.create_offspring(),
+            genome,
             response_clusters: responses,
+            wing_size,
+            rng,
+        }
+    }
+
+    pub fn get_lineage(&self) -> &Lineage {
+        &self.lineage
+    }
+
+    // the plant doesn't know where it is stored, so the state has to be given by the habitat
+    pub fn get_info(&self, state: PlantState) -> PlantInfo {
+        PlantInfo {
+            lineage: self.lineage,
+            species_id: self.species_id,
+            state,
+            pos: self.pos,
+            energy: self.energy,
+            lifetime: self.lifetime,
+            cell_count: self.cells.len(),
+            wing_size: self.wing_size,
         }
     }
 
+    #[cfg(feature = "viewer")]
+    pub fn get_species_id(&self) -> Option<u64> {
+        self.species_id
+    }
+
+    pub fn set_species_id(&mut self, species_id: u64) {
+        self.species_id = Some(species_id);
+    }
+
+    pub fn get_genome(&self) -> &Genome {
+        &self.genome
+    }
+
+    pub fn get_genome_report(&self, config: &SimulationConfig) -> GenomeReport {
+        GenomeReport::new(self.lineage.id, self.genome.len(), &self.response_clusters, self.wing_size, config)
+    }
+
     pub fn get_cells(&self) -> Vec<(IVec2, CellType)> {
         let mut out = Vec::<(IVec2, CellType)>::with_capacity(self.cells.len());
         for cell in &self.cells {
@@ -70,6 +171,12 @@ impl Plant {
         out
     }
 
+    // the height of the highest cell above the root, a plant with only a root has a height of 1,
+    // so plants on hills aren't taller than the ones in valleys
+    pub fn get_height(&self) -> i32 {
+        self.cells.iter().map(|cell| cell.get_pos().y - self.pos.y + 1).max().unwrap_or(0)
+    }
+
     pub fn get_cell_ix_at(&self, pos: IVec2) -> Option<usize> {
         for (ix, cell) in self.cells.iter().enumerate() {
             if cell.get_pos() == pos {
@@ -83,6 +190,10 @@ impl Plant {
         self.energy > 0f32
     }
 
+    pub fn get_wing_size(&self) -> f32 {
+        self.wing_size
+    }
+
     pub fn get_energy(&self) -> f32 {
         self.energy
     }
@@ -91,26 +202,36 @@ impl Plant {
         self.energy += energy_amount;
     }
 
+    pub fn get_nutrients(&self) -> f32 {
+        self.nutrients
+    }
+
+    pub fn give_nutrients(&mut self, amount: f32) {
+        self.nutrients += amount;
+    }
+
     pub fn increase_age(&mut self) {
         self.lifetime += 1;
     }
 
-    pub fn is_dead(&self) -> bool {
-        self.is_too_old() || !self.has_root
+    pub fn is_dead(&self, config: &SimulationConfig) -> bool {
+        self.is_too_old(config) || !self.has_root
     }
 
-    fn is_too_old(&self) -> bool {
-        self.lifetime > BASE_MAX_AGE + MAX_AGE_CELL_MODIFIER * self.cells.len()
+    fn is_too_old(&self, config: &SimulationConfig) -> bool {
+        self.lifetime > config.base_max_age + config.max_age_cell_modifier * self.cells.len()
     }
-    pub fn create_growth_proposals(&self) -> Vec<(IVec2, usize, CellType, f32)> {
+    // ground_height holds the height of the ground of every column, everything below it is soil
+    pub fn create_growth_proposals(&self, config: &SimulationConfig, ground_height: &[i32]) -> Vec<(IVec2, usize, CellType, f32)> {
         // if plant has a root and can grow
         if self.has_root {
             let mut growth_proposals: Vec<(IVec2, usize, CellType, f32)> = Vec::new();
             for cell in self.cells.iter() {
                 // only trunks can grow other trunks or leaves
                 match cell.get_cell_type() {
-                    // this is the parent for the growth proposals
-                    Trunk { root_connection } => {
+                    // this is the parent for the growth proposals, roots grow like trunks but stay below the ground
+                    Trunk { root_connection } | Root { root_connection } => {
+                        let is_root = matches!(cell.get_cell_type(), Root { .. });
                         // we take the index of the response cluster
                         let idx = cell.get_response_ix();
                         // and use the index to get the corresponding cluster from the plant
@@ -121,19 +242,34 @@ impl Plant {
                                 &cell.get_pos().into(),
                                 self.energy,
                                 root_connection,
+                                config,
                             )
                         {
-                            // update root connection if cell_type is a trunk
+                            let below_ground = pos.y < ground_height[pos.x.rem_euclid(ground_height.len() as i32) as usize];
+                            // roots only grow into the soil
+                            if is_root && !below_ground {
+                                continue;
+                            }
+                            // everything growing below the ground becomes a root
+                            if below_ground {
+                                cell_type = Root { root_connection };
+                            }
+                            // update root connection if cell_type is a trunk or root
                             match cell_type {
                                 Trunk { root_connection } => {
                                     cell_type = Trunk {
-                                        root_connection: root_connection * ROOT_CON_DECAY,
+                                        root_connection: root_connection * config.root_con_decay,
+                                    };
+                                }
+                                Root { root_connection } => {
+                                    cell_type = Root {
+                                        root_connection: root_connection * config.root_con_decay,
                                     };
                                 }
                                 _ => {}
                             }
                             // calculate the energy cost for growing
-                            let energy_cost = CELL_GROWTH_COST / root_connection;
+                            let energy_cost = config.cell_growth_cost / root_connection;
                             // add the responses as growth proposals
                             growth_proposals.push((pos, response_idx, cell_type, energy_cost));
                         }
@@ -147,15 +283,94 @@ impl Plant {
             Vec::new()
         }
     }
+    // removes every leaf for which dies returns true and returns the positions of the removed leaves
+    pub fn remove_leaves(&mut self, mut dies: impl FnMut() -> bool) -> Vec<IVec2> {
+        let mut removed = Vec::new();
+        self.cells.retain(|cell| match cell.get_cell_type() {
+            Leaf { .. } if dies() => {
+                removed.push(cell.get_pos());
+                false
+            }
+            _ => true,
+        });
+        removed
+    }
+
+    // every cell carries its own weight and the load of the cells it supports, a trunk can carry
+    // trunk_load_capacity times its root connection, cells which aren't above their supporting trunk
+    // weigh overhang_load_factor times as much, the overloaded trunks closest to the root break off
+    // together with everything they support, returns the positions of the broken cells
+    pub fn break_overloaded(&mut self, config: &SimulationConfig) -> Vec<IVec2> {
+        if config.trunk_load_capacity <= 0. || !self.has_root {
+            return Vec::new();
+        }
+        // the root is always the first cell, the supporting structure is found by a breadth first search
+        // from the root, only trunks support other cells
+        let cell_ix: HashMap<IVec2, usize> = self.cells.iter().enumerate()
+            .map(|(ix, cell)| (cell.get_pos(), ix))
+            .collect();
+        let mut parent: Vec<Option<usize>> = vec![None; self.cells.len()];
+        let mut visited = vec![false; self.cells.len()];
+        let mut order = vec![0];
+        visited[0] = true;
+        let mut next = 0;
+        while next < order.len() {
+            let ix = order[next];
+            next += 1;
+            if let Trunk { .. } = self.cells[ix].get_cell_type() {
+                for direction in [IVec2::Y, IVec2::X, IVec2::NEG_Y, IVec2::NEG_X] {
+                    if let Some(&neighbor) = cell_ix.get(&(self.cells[ix].get_pos() + direction)) {
+                        // roots are held by the soil and don't weigh on the trunks
+                        if !visited[neighbor] && !matches!(self.cells[neighbor].get_cell_type(), Root { .. }) {
+                            visited[neighbor] = true;
+                            parent[neighbor] = Some(ix);
+                            order.push(neighbor);
+                        }
+                    }
+                }
+            }
+        }
+
+        // the loads get passed down from the outermost cells to the root
+        let mut load = vec![1f32; self.cells.len()];
+        for &ix in order.iter().rev() {
+            if let Some(parent_ix) = parent[ix] {
+                let supported_from_below = self.cells[ix].get_pos().y == self.cells[parent_ix].get_pos().y + 1;
+                let factor = if supported_from_below { 1. } else { config.overhang_load_factor };
+                load[parent_ix] += load[ix] * factor;
+            }
+        }
+
+        // parents come first in the search order, so a broken trunk takes all cells it supports with it
+        let mut broken = vec![false; self.cells.len()];
+        for &ix in order.iter().skip(1) {
+            let overloaded = match self.cells[ix].get_cell_type() {
+                Trunk { root_connection } => load[ix] > config.trunk_load_capacity * root_connection,
+                _ => false,
+            };
+            broken[ix] = overloaded || parent[ix].is_some_and(|parent_ix| broken[parent_ix]);
+        }
+
+        let mut removed = Vec::new();
+        let mut ix = 0;
+        self.cells.retain(|cell| {
+            ix += 1;
+            if broken[ix - 1] {
+                removed.push(cell.get_pos());
+            }
+            !broken[ix - 1]
+        });
+        removed
+    }
+
     pub fn add_cell(&mut self, pos: IVec2, cell: CellType, response_ix: usize) {
         self.cells.push(PlantCell::new(pos, cell, response_ix));
     }
 
-    // create an array of new response clusters with a size of RESPONSE_SIZE
-    fn create_responses(genome: &mut Genome) -> Vec<ResponseCluster> {
-        (0..RESPONSE_SIZE)
-            .into_iter()
-            .map(|x| ResponseCluster::new(genome))
+    // create an array of new response clusters with a size of config.response_size
+    fn create_responses(genome: &mut Genome, config: &SimulationConfig) -> Vec<ResponseCluster> {
+        (0..config.response_size)
+            .map(|_| ResponseCluster::new(genome, config))
             .collect()
     }
 
@@ -167,8 +382,9 @@ impl Plant {
         self.pos
     }
 
+    // the seed has to be placed on the ground of its column before
     pub fn create_root(&mut self) {
-        assert_eq!(self.pos.y, 0);
+        assert!(!self.has_root);
         self.has_root = true;
         self.cells.push(PlantCell::new_root(self.pos));
     }
@@ -178,3 +394,90 @@ impl Plant {
         self.pos = pos;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trunk(root_connection: f32) -> CellType {
+        Trunk { root_connection }
+    }
+
+    // a plant rooted at (10, 0) with the given cells grown on top of its root cell
+    fn plant_with(cells: &[(i32, i32, CellType)]) -> Plant {
+        let mut plant = Plant::new(IVec2::new(10, 0), &SimulationConfig::default(), 0, Lineage::new_origin(0, 0));
+        plant.create_root();
+        for &(x, y, cell) in cells {
+            plant.add_cell(IVec2::new(x, y), cell, 0);
+        }
+        plant
+    }
+
+    fn load_config(trunk_load_capacity: f32) -> SimulationConfig {
+        SimulationConfig { trunk_load_capacity, overhang_load_factor: 2., ..Default::default() }
+    }
+
+    fn positions(plant: &Plant) -> Vec<IVec2> {
+        plant.get_cells().into_iter().map(|(pos, _)| pos).collect()
+    }
+
+    // a trunk going right from the stem, the overhanging cells weigh twice as much,
+    // so the stem carries 11 cells and the first overhanging trunk 5
+    fn overhanging_plant() -> Plant {
+        plant_with(&[
+            (10, 1, trunk(1.)),
+            (11, 1, trunk(0.25)),
+            (12, 1, trunk(0.25)),
+            (12, 2, CellType::new_leaf()),
+        ])
+    }
+
+    #[test]
+    fn straight_trunk_within_capacity_stays() {
+        let mut plant = plant_with(&[
+            (10, 1, trunk(1.)),
+            (10, 2, trunk(1.)),
+            (10, 3, trunk(1.)),
+            (10, 4, trunk(1.)),
+            (10, 5, CellType::new_leaf()),
+        ]);
+        // the lowest trunk carries exactly 5 cells, itself included
+        assert!(plant.break_overloaded(&load_config(5.)).is_empty());
+        assert_eq!(plant.get_cells().len(), 6);
+
+        let broken = plant.break_overloaded(&load_config(4.));
+        assert_eq!(broken.len(), 5);
+        assert_eq!(positions(&plant), vec![IVec2::new(10, 0)]);
+    }
+
+    #[test]
+    fn overhang_breaks_with_everything_it_supports() {
+        let mut plant = overhanging_plant();
+        let broken = plant.break_overloaded(&load_config(12.));
+        // (12, 1) only carries 2 cells, but it hangs on the overloaded trunk
+        assert_eq!(broken, vec![IVec2::new(11, 1), IVec2::new(12, 1), IVec2::new(12, 2)]);
+        assert_eq!(positions(&plant), vec![IVec2::new(10, 0), IVec2::new(10, 1)]);
+    }
+
+    #[test]
+    fn zero_capacity_disables_breaking() {
+        let mut plant = overhanging_plant();
+        assert!(plant.break_overloaded(&load_config(0.)).is_empty());
+        assert_eq!(plant.get_cells().len(), 5);
+    }
+
+    #[test]
+    fn roots_never_break() {
+        let root = Root { root_connection: 1. };
+        let mut plant = plant_with(&[
+            (10, -1, root),
+            (10, -2, root),
+            (10, 1, trunk(1.)),
+            (11, 1, trunk(1.)),
+            (11, 0, root),
+        ]);
+        let broken = plant.break_overloaded(&load_config(0.5));
+        assert_eq!(broken, vec![IVec2::new(10, 1), IVec2::new(11, 1)]);
+        assert_eq!(positions(&plant), vec![IVec2::new(10, 0), IVec2::new(10, -1), IVec2::new(10, -2), IVec2::new(11, 0)]);
+    }
+}
@@ -1,6 +1,8 @@
-use macroquad::math::IVec2;
+use glam::IVec2;
+use serde::{Deserialize, Serialize};
 use crate::sim_mod::cell_types::CellType;
 
+#[derive(Serialize, Deserialize)]
 pub struct PlantCell {
     pos: IVec2,
     cell_type: CellType,
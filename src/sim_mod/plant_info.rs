@@ -0,0 +1,23 @@
+use glam::IVec2;
+use serde::{Deserialize, Serialize};
+use crate::sim_mod::lineage::Lineage;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PlantState {
+    Airborne,   // a seed which is still falling
+    Grounded,   // a seed waiting in the ground for a free spot
+    Living,     // a plant which has grown a root
+}
+
+// a read only view of a single plant, returned by the plant queries of the habitat
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PlantInfo {
+    pub lineage: Lineage,
+    pub species_id: Option<u64>,    // None until the plant (or its ancestor) was part of a species clustering
+    pub state: PlantState,
+    pub pos: IVec2,             // the root position of living plants, the seed position otherwise
+    pub energy: f32,
+    pub lifetime: usize,
+    pub cell_count: usize,
+    pub wing_size: f32,         // the seed trait of the genome, from 0 to 1
+}
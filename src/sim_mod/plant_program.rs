@@ -0,0 +1,284 @@
+use std::fmt::{Display, Formatter};
+use crate::sim_mod::genome::Genome;
+use crate::sim_mod::simulation_config::SimulationConfig;
+
+// the amount of genome values a single Response parses
+const VALUES_PER_RESPONSE: usize = 5;
+
+const DIRECTIONS: [&str; 4] = ["up", "right", "down", "left"];
+
+// hand-authored plant programs, one line per response cluster:
+//
+//   # comments start with #
+//   cluster 0: up -> trunk@1, left -> leaf if y>5
+//   cluster 1: up -> trunk@1 bias 2, right -> leaf
+//   wings: 0.5
+//
+// a response is "<direction> -> <leaf|trunk>[@<cluster>] [if y>N | if y>=N] [bias F]",
+// the cluster defaults to 0 and the bias to 1, responses and clusters which are not mentioned never grow,
+// the wing size of the seeds ranges from 0 to 1 and defaults to 0
+impl Genome {
+    // encodes the program into a genome which decodes to exactly the described response clusters,
+    // the encoding depends on the grid height and response size of the config
+    pub fn from_program(source: &str, config: &SimulationConfig) -> Result<Genome, ProgramError> {
+        let mut data: Vec<u16> = (0..config.response_size * DIRECTIONS.len())
+            .flat_map(|_| ProgramResponse::DISABLED_VALUES)
+            .collect();
+        // the wing size follows the response clusters
+        data.push(0);
+        for (line_ix, line) in source.lines().enumerate() {
+            let line_nr = line_ix + 1;
+            let line = line.split('#').next().unwrap().trim();
+            if line.is_empty() {
+                continue;
+            }
+            if let Some(wings) = line.strip_prefix("wings:") {
+                let wing_size = wings.trim().parse::<f32>().ok().filter(|x| (0.0..=1.0).contains(x))
+                    .ok_or_else(|| ProgramError { line: line_nr, message: format!("invalid wing size '{}', has to be between 0 and 1", wings.trim()) })?;
+                *data.last_mut().unwrap() = ProgramResponse::encode_fraction(wing_size);
+                continue;
+            }
+            let (cluster, responses) = parse_cluster_line(line, config)
+                .map_err(|message| ProgramError { line: line_nr, message })?;
+            for (direction_ix, response) in responses {
+                let start = (cluster * DIRECTIONS.len() + direction_ix) * VALUES_PER_RESPONSE;
+                data[start..start + VALUES_PER_RESPONSE].copy_from_slice(&response.to_values(config));
+            }
+        }
+        Ok(Genome::from_values(data).expect("the response size is greater than 0"))
+    }
+}
+
+struct ProgramResponse {
+    leaf: bool,
+    target_cluster: usize,
+    height_threshold: i32,
+    bias: f32,
+}
+
+impl ProgramResponse {
+    // a height threshold at the top of the grid can never be reached
+    const DISABLED_VALUES: [u16; VALUES_PER_RESPONSE] = [0, u16::MAX, 0, u16::MAX, 0];
+
+    // the values in the order Response::new parses them
+    fn to_values(&self, config: &SimulationConfig) -> [u16; VALUES_PER_RESPONSE] {
+        let (height, height_chance) = match self.height_threshold {
+            0 => (0, u16::MAX),
+            // the middle of the range which decodes to the threshold, the chance value 0 always activates it
+            y => (Self::encode_fraction((y as f32 + 0.5) / config.grid_size.y as f32), 0),
+        };
+        [
+            self.target_cluster as u16,
+            height,
+            height_chance,
+            Self::encode_fraction(1. / self.bias).max(1),
+            if self.leaf { u16::MAX } else { 0 },
+        ]
+    }
+
+    fn encode_fraction(value: f32) -> u16 {
+        (value.clamp(0., 1.) * u16::MAX as f32).round() as u16
+    }
+}
+
+// "cluster N: response, response, ..."
+fn parse_cluster_line(line: &str, config: &SimulationConfig) -> Result<(usize, Vec<(usize, ProgramResponse)>), String> {
+    let (head, body) = line.split_once(':').ok_or("expected 'cluster <index>: <responses>' or 'wings: <size>'")?;
+    let cluster = match head.split_whitespace().collect::<Vec<_>>().as_slice() {
+        ["cluster", index] => parse_cluster_index(index, config)?,
+        _ => return Err(format!("expected 'cluster <index>', got '{}'", head.trim())),
+    };
+    let mut responses = Vec::new();
+    for response in body.split(',').map(str::trim).filter(|x| !x.is_empty()) {
+        let parsed = parse_response(response, config)?;
+        if responses.iter().any(|(direction_ix, _)| *direction_ix == parsed.0) {
+            return Err(format!("direction '{}' is used twice in cluster {cluster}", DIRECTIONS[parsed.0]));
+        }
+        responses.push(parsed);
+    }
+    Ok((cluster, responses))
+}
+
+// "direction -> leaf|trunk[@cluster] [if y>N] [bias F]"
+fn parse_response(response: &str, config: &SimulationConfig) -> Result<(usize, ProgramResponse), String> {
+    let (direction, rest) = response.split_once("->")
+        .ok_or_else(|| format!("expected '<direction> -> <cell type>', got '{response}'"))?;
+    let direction_ix = DIRECTIONS.iter().position(|x| *x == direction.trim())
+        .ok_or_else(|| format!("unknown direction '{}', expected up, right, down or left", direction.trim()))?;
+
+    let mut tokens = rest.split_whitespace().peekable();
+    let target = tokens.next().ok_or("missing cell type")?;
+    let (cell_type, target_cluster) = match target.split_once('@') {
+        Some((cell_type, index)) => (cell_type, parse_cluster_index(index, config)?),
+        None => (target, 0),
+    };
+    let leaf = match cell_type {
+        "leaf" => true,
+        "trunk" => false,
+        _ => return Err(format!("unknown cell type '{cell_type}', expected leaf or trunk")),
+    };
+
+    let mut out = ProgramResponse { leaf, target_cluster, height_threshold: 0, bias: 1. };
+    while let Some(token) = tokens.next() {
+        match token {
+            "if" => {
+                // the condition may contain spaces, it ends at the next keyword
+                let mut condition = String::new();
+                while let Some(part) = tokens.next_if(|x| *x != "bias") {
+                    condition.push_str(part);
+                }
+                out.height_threshold = parse_condition(&condition, config)?;
+            }
+            "bias" => {
+                let bias = tokens.next().ok_or("missing value after 'bias'")?;
+                out.bias = bias.parse::<f32>().ok().filter(|x| *x >= 1.)
+                    .ok_or_else(|| format!("invalid bias '{bias}', has to be a number of at least 1"))?;
+            }
+            _ => return Err(format!("unexpected '{token}', expected 'if' or 'bias'")),
+        }
+    }
+    Ok((direction_ix, out))
+}
+
+// "y>N" or "y>=N", returns the height threshold
+fn parse_condition(condition: &str, config: &SimulationConfig) -> Result<i32, String> {
+    let (offset, value) = if let Some(value) = condition.strip_prefix("y>=") {
+        (0, value)
+    } else if let Some(value) = condition.strip_prefix("y>") {
+        (1, value)
+    } else {
+        return Err(format!("invalid condition '{condition}', expected 'y>N' or 'y>=N'"));
+    };
+    let threshold = value.parse::<i32>().map_err(|_| format!("invalid height '{value}'"))? + offset;
+    if threshold < 0 || threshold >= config.grid_size.y {
+        return Err(format!("height threshold {threshold} lies outside of the grid"));
+    }
+    Ok(threshold)
+}
+
+fn parse_cluster_index(index: &str, config: &SimulationConfig) -> Result<usize, String> {
+    match index.trim().parse::<usize>() {
+        Ok(x) if x < config.response_size => Ok(x),
+        _ => Err(format!("invalid cluster '{}', expected 0 to {}", index.trim(), config.response_size - 1)),
+    }
+}
+
+#[derive(Debug)]
+pub struct ProgramError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl Display for ProgramError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "plant program line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for ProgramError {}
+
+#[cfg(test)]
+mod tests {
+    use glam::IVec2;
+    use super::*;
+    use crate::sim_mod::cell_types::CellType;
+    use crate::sim_mod::lineage::Lineage;
+    use crate::sim_mod::plant::Plant;
+    use crate::sim_mod::response_cluster::ResponseCluster;
+
+    fn test_config() -> SimulationConfig {
+        SimulationConfig {
+            response_size: 4,
+            ..SimulationConfig::with_grid_size(IVec2::new(64, 32))
+        }
+    }
+
+    fn decode(source: &str, config: &SimulationConfig) -> Vec<ResponseCluster> {
+        let mut genome = Genome::from_program(source, config).unwrap();
+        (0..config.response_size).map(|_| ResponseCluster::new(&mut genome, config)).collect()
+    }
+
+    // the positions the cluster grows into from a cell at the given height, with plenty of energy
+    fn growths_at(cluster: &ResponseCluster, y: i32, config: &SimulationConfig) -> Vec<IVec2> {
+        cluster.get_response_ix_with_position(&IVec2::new(10, y), 1000., 1., config)
+            .into_iter()
+            .map(|(pos, _, _)| pos)
+            .collect()
+    }
+
+    fn error_of(source: &str) -> ProgramError {
+        Genome::from_program(source, &test_config()).unwrap_err()
+    }
+
+    #[test]
+    fn height_condition_fires_above_the_threshold() {
+        let config = test_config();
+        let clusters = decode("cluster 0: left -> leaf if y>5", &config);
+        assert_eq!(clusters[0].get_response("left").get_height_threshold(), 6);
+        assert!(matches!(clusters[0].get_response("left").get_cell_type(), CellType::Leaf { .. }));
+        assert!(growths_at(&clusters[0], 5, &config).is_empty());
+        assert_eq!(growths_at(&clusters[0], 6, &config), vec![IVec2::new(9, 6)]);
+
+        let clusters = decode("cluster 0: up -> leaf if y >= 3", &config);
+        assert_eq!(clusters[0].get_response("up").get_height_threshold(), 3);
+    }
+
+    #[test]
+    fn responses_decode_target_and_bias() {
+        let config = test_config();
+        let clusters = decode("cluster 1: up -> trunk@2 bias 2, right -> leaf", &config);
+        let up = clusters[1].get_response("up");
+        assert!(matches!(up.get_cell_type(), CellType::Trunk { .. }));
+        assert_eq!(up.get_cluster_index(), 2);
+        assert!((up.get_growth_bias_factor() - 2.).abs() < 0.01);
+        assert_eq!(up.get_height_threshold(), 0);
+        let right = clusters[1].get_response("right");
+        assert_eq!(right.get_cluster_index(), 0);
+        assert!((right.get_growth_bias_factor() - 1.).abs() < 0.01);
+        assert_eq!(growths_at(&clusters[1], 0, &config), vec![IVec2::new(10, 1), IVec2::new(11, 0)]);
+    }
+
+    #[test]
+    fn unmentioned_responses_never_grow() {
+        let config = test_config();
+        let clusters = decode("cluster 1: up -> trunk", &config);
+        for y in [0, config.grid_size.y - 1] {
+            assert!(growths_at(&clusters[0], y, &config).is_empty());
+            assert_eq!(growths_at(&clusters[1], y, &config).len(), 1);
+        }
+    }
+
+    #[test]
+    fn wings_end_up_in_the_genome_report() {
+        let config = test_config();
+        let report = |source: &str| {
+            let genome = Genome::from_program(source, &config).unwrap();
+            Plant::new_with_genome(IVec2::ZERO, &config, 0, Lineage::new_origin(0, 0), genome)
+                .get_genome_report(&config)
+        };
+        assert!((report("cluster 0: up -> trunk\nwings: 0.5").wing_size - 0.5).abs() < 0.001);
+        assert_eq!(report("cluster 0: up -> trunk").wing_size, 0.);
+        let clusters = report("# a comment\n\ncluster 0: up -> trunk@1 # trailing\ncluster 1: up -> leaf").clusters;
+        assert!(clusters[0].reachable && clusters[1].reachable && !clusters[2].reachable);
+    }
+
+    #[test]
+    fn invalid_programs_name_the_line() {
+        let error = error_of("cluster 0: up -> trunk\ncluster 1: up -> leaf, up -> trunk");
+        assert_eq!(error.line, 2);
+        assert!(error.message.contains("used twice"), "{error}");
+
+        assert!(error_of("cluster 4: up -> trunk").message.contains("invalid cluster"));
+        assert!(error_of("cluster 0: up -> trunk@9").message.contains("invalid cluster"));
+        assert!(error_of("cluster 0: up -> leaf if y>40").message.contains("outside of the grid"));
+        assert!(error_of("cluster 0: up -> leaf if y>-3").message.contains("outside of the grid"));
+        assert!(error_of("cluster 0: up -> leaf if x>3").message.contains("invalid condition"));
+        assert!(error_of("cluster 0: up -> leaf bias 0.5").message.contains("invalid bias"));
+        assert!(error_of("cluster 0: up -> leaf bias fast").message.contains("invalid bias"));
+        assert!(error_of("cluster 0: up -> leaf bias").message.contains("missing value"));
+        assert!(error_of("cluster 0: sideways -> leaf").message.contains("unknown direction"));
+        assert!(error_of("cluster 0: up -> flower").message.contains("unknown cell type"));
+        assert!(error_of("wings: 2").message.contains("invalid wing size"));
+        assert!(error_of("up -> leaf").message.contains("expected"));
+    }
+}
@@ -1,8 +1,10 @@
-use macroquad::math::IVec2;
+use glam::IVec2;
+use serde::{Deserialize, Serialize};
 use crate::sim_mod::cell_types::CellType;
-use crate::constants::simulation::{CELL_GROWTH_COST, GRID_SIZE, HEIGHT_THRESHOLD_CHANCE, RESPONSE_SIZE};
 use crate::sim_mod::genome::Genome;
+use crate::sim_mod::simulation_config::SimulationConfig;
 
+#[derive(Serialize, Deserialize)]
 pub struct Response {
     target_cluster_index: usize,
     height_threshold: i32,
@@ -11,13 +13,13 @@ pub struct Response {
 }
 
 impl Response {
-    pub fn new(genome: &mut Genome) -> Response {
+    pub fn new(genome: &mut Genome, config: &SimulationConfig) -> Response {
         Response {
-            target_cluster_index: Self::parse_cluster_index(genome.parse_value()),
+            target_cluster_index: Self::parse_cluster_index(genome.parse_value(), config.response_size),
             height_threshold: {
                 let value = genome.parse_value_normalized();
                 match genome.parse_value_normalized() {
-                    n if n <= HEIGHT_THRESHOLD_CHANCE => (value * GRID_SIZE.y as f32) as i32,
+                    n if n <= config.height_threshold_chance => (value * config.grid_size.y as f32) as i32,
                     _ => 0
                 }
             },
@@ -38,9 +40,9 @@ impl Response {
         self.target_cluster_index
     }
 
-    pub fn is_active(&self, pos: &IVec2, current_energy: f32, root_connection: f32) -> bool {
+    pub fn is_active(&self, pos: &IVec2, current_energy: f32, root_connection: f32, config: &SimulationConfig) -> bool {
         let height_reached = self.height_threshold == 0 || pos.y >= self.height_threshold;
-        let enough_energy = current_energy >= CELL_GROWTH_COST
+        let enough_energy = current_energy >= config.cell_growth_cost
             / self.growth_bias_factor
             / root_connection;
 
@@ -51,9 +53,17 @@ impl Response {
         self.target_cell_type
     }
 
-    //gives a random number 0<=x<Response_SIZE to act as a random index for all response clusters of a plant
-    fn parse_cluster_index(x: u16) -> usize {
-        x as usize % RESPONSE_SIZE
+    pub fn get_height_threshold(&self) -> i32 {
+        self.height_threshold
+    }
+
+    pub fn get_growth_bias_factor(&self) -> f32 {
+        self.growth_bias_factor
+    }
+
+    //gives a random number 0<=x<response_size to act as a random index for all response clusters of a plant
+    fn parse_cluster_index(x: u16, response_size: usize) -> usize {
+        x as usize % response_size
     }
 
 }
\ No newline at end of file
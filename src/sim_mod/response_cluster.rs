@@ -1,19 +1,22 @@
-use macroquad::prelude::IVec2;
+use glam::IVec2;
+use serde::{Deserialize, Serialize};
 use crate::sim_mod::cell_types::CellType;
 use crate::sim_mod::genome::Genome;
 use crate::sim_mod::response::Response;
+use crate::sim_mod::simulation_config::SimulationConfig;
 
+#[derive(Serialize, Deserialize)]
 pub struct ResponseCluster {
     responses: [Response; 4],
 }
 
 impl ResponseCluster {
-    pub fn new(genome: &mut Genome) -> ResponseCluster {
+    pub fn new(genome: &mut Genome, config: &SimulationConfig) -> ResponseCluster {
         let responses = [
-            Response::new(genome),
-            Response::new(genome),
-            Response::new(genome),
-            Response::new(genome)
+            Response::new(genome, config),
+            Response::new(genome, config),
+            Response::new(genome, config),
+            Response::new(genome, config)
         ];
         ResponseCluster {
             responses
@@ -21,31 +24,31 @@ impl ResponseCluster {
     }
 
     pub fn get_response_ix_with_position
-        (&self, pos: &IVec2, current_energy: f32, root_connection: f32)
+        (&self, pos: &IVec2, current_energy: f32, root_connection: f32, config: &SimulationConfig)
         -> Vec<(IVec2, usize, CellType)> {
 
         let mut out : Vec<(IVec2, usize, CellType)> = Vec::with_capacity(4);
 
         let (x, y) = (*pos).into();
-        if self.responses[0].is_active(&pos, current_energy, root_connection) {
+        if self.responses[0].is_active(pos, current_energy, root_connection, config) {
             out.push((
                 IVec2::new(x, y+1),
                 self.responses[0].get_cluster_index(),
                 self.responses[0].get_cell_type()));
         }
-        if self.responses[1].is_active(&pos, current_energy, root_connection) {
+        if self.responses[1].is_active(pos, current_energy, root_connection, config) {
             out.push((
                 IVec2::new(x+1, y),
                 self.responses[1].get_cluster_index(),
                 self.responses[1].get_cell_type()));
         }
-        if self.responses[2].is_active(&pos, current_energy, root_connection) {
+        if self.responses[2].is_active(pos, current_energy, root_connection, config) {
             out.push((
                 IVec2::new(x, y-1),
                 self.responses[2].get_cluster_index(),
                 self.responses[2].get_cell_type()));
         }
-        if self.responses[3].is_active(&pos, current_energy, root_connection) {
+        if self.responses[3].is_active(pos, current_energy, root_connection, config) {
             out.push((
                 IVec2::new(x-1, y),
                 self.responses[3].get_cluster_index(),
@@ -54,6 +57,11 @@ impl ResponseCluster {
 
         out
     }
+    // the responses in the order up, right, down, left
+    pub fn get_responses(&self) -> &[Response; 4] {
+        &self.responses
+    }
+
     pub fn get_response(&self, direction: &str) -> &Response {
         match direction {
             "up" => &self.responses[0],
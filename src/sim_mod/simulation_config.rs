@@ -0,0 +1,398 @@
+use std::fmt::{Display, Formatter};
+use std::path::Path;
+use glam::IVec2;
+use serde::{Deserialize, Serialize};
+use crate::sim_mod::genome::Crossover;
+use crate::constants::simulation::{BASE_MAX_AGE, CELL_GROWTH_COST, CROSSOVER, CROSSOVER_POINTS, CELL_SUSTAIN_ENERGY_COST, CREEP_RATE, CREEP_SIZE, DEAD_CELL_REMOVE_RATE, DEFAULT_ENERGY, DELETION_RATE, DUPLICATION_RATE, GENOME_SIZE, GRID_SIZE, HEIGHT_THRESHOLD_CHANCE, INSERTION_RATE, INVERSION_RATE, LEAF_ABSORB_RATE, LIFETIME_FACTOR, MAX_AGE_CELL_MODIFIER, MAX_GENOME_LENGTH, MAX_SEGMENT_LENGTH, DAY_LENGTH, NIGHT_FRACTION, SUN_ANGLE, SUN_ANGLE_SWEEP, YEAR_LENGTH, SEASON_SUN_AMPLITUDE, SEASON_SUSTAIN_AMPLITUDE, GERMINATION_THRESHOLD, WINTER_THRESHOLD, WINTER_LEAF_LOSS, TRUNK_LOAD_CAPACITY, OVERHANG_LOAD_FACTOR, TERRAIN_HEIGHT, TERRAIN_HILLS, TERRAIN_CLIFFS, SOIL_DEPTH, SOIL_CAPACITY, SOIL_REGENERATION, ROOT_UPTAKE_RATE, CELL_NUTRIENT_COST, SEED_NUTRIENTS, DECAY_NUTRIENTS, LITTER_SEEP_RATE, SEED_LITTER_UPTAKE, RAIN_RATE, RAIN_AMOUNT, INFILTRATION_RATE, MOISTURE_CAPACITY, MOISTURE_DIFFUSION, EVAPORATION_RATE, ROOT_WATER_UPTAKE, LEAF_WATER_DEMAND, DROUGHT_ENERGY_COST, DROUGHT_LEAF_LOSS, WIND_SPEED, WIND_GUST_STRENGTH, WIND_SHEAR, WING_GLIDE, WING_ENERGY_COST, MAX_GROWTHS_PER_ITERATION, MUTATION_RATE, POLLINATION_RANGE, POLLINATION_RATE, RESPONSE_SIZE, ROOT_CON_DECAY, SEED_ENERGY_DRAIN, SEED_SPAWN_RATE, SPECIES_INTERVAL, SPECIES_THRESHOLD, SUN_POWER, TRUNK_ABSORB_RATE};
+
+// all tunable parameters of a simulation, owned by the habitat and handed down to plants and genomes
+// the defaults are the values of constants::simulation
+// missing keys in a config file fall back to the defaults, unknown keys are rejected
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SimulationConfig {
+    pub grid_size: IVec2,
+    pub root_con_decay: f32,            // how fast the connection to the root decays over distance
+    pub trunk_absorb_rate: f32,         // how much sun gets blocked by trunks
+    pub leaf_absorb_rate: f32,          // how much sun gets absorbed by leaves
+    pub genome_size: usize,             // the size of random genomes, only response_size * 20 + 1 values get decoded
+    pub mutation_rate: f64,             // the probability that a genome value mutates
+    pub creep_rate: f64,                // the probability that a genome value gets changed by a small delta
+    pub creep_size: u16,                // the max delta of a creep mutation
+    pub duplication_rate: f64,          // the probability that an offspring duplicates a genome segment
+    pub inversion_rate: f64,            // the probability that an offspring reverses a genome segment
+    pub insertion_rate: f64,            // the probability that an offspring gets a random segment inserted
+    pub deletion_rate: f64,             // the probability that an offspring looses a genome segment
+    pub max_segment_length: usize,      // the max length of a duplicated, inverted, inserted or deleted segment
+    pub max_genome_length: usize,       // duplications and insertions can't grow a genome beyond this length
+    pub response_size: usize,           // the amount of response clusters a genome can hold
+    pub height_threshold_chance: f32,   // chance that a Response is locked behind a height threshold
+    pub cell_growth_cost: f32,
+    pub max_growths_per_iteration: usize, // each iteration each plant can grow this amount of cells at max
+    pub cell_sustain_energy_cost: f32,
+    pub default_energy: f32,            // the default energy each plant starts with
+    pub sun_power: f32,
+    pub sun_angle: f32,                 // the angle of the sun rays at noon in degrees, 0 shines straight down
+    pub day_length: u64,                // the amount of updates of a whole day, 0 means it is always noon
+    pub night_fraction: f32,            // the share of a day without any sunlight
+    pub sun_angle_sweep: f32,           // how far the angle of the sun differs from noon at sunrise and sunset
+    pub year_length: u64,               // the amount of updates of a whole year, 0 disables the seasons
+    pub season_sun_amplitude: f32,      // the sun power changes by this share between summer and winter
+    pub season_sustain_amplitude: f32,  // the sustain cost of cells changes by this share, it is highest in winter
+    pub germination_threshold: f32,     // seeds only grow a root while the season is at least this value
+    pub winter_threshold: f32,          // the season is winter while it is below this value
+    pub winter_leaf_loss: f32,          // the chance of every leaf to die in each update of the winter
+    pub trunk_load_capacity: f32,       // the amount of cells a trunk next to the root can carry, 0 disables gravity
+    pub overhang_load_factor: f32,      // how much heavier cells weigh when they aren't supported from below
+    pub terrain_height: i32,            // the max height of the ground above the bottom of the grid, 0 keeps the ground flat
+    pub terrain_hills: u32,             // the amount of big hills along the grid
+    pub terrain_cliffs: u32,            // the amount of plateaus and basins, which have cliffs on both sides
+    pub soil_depth: i32,                // the amount of soil rows below the ground, 0 disables roots and nutrients
+    pub soil_capacity: f32,             // the max amount of nutrients a soil cell can hold
+    pub soil_regeneration: f32,         // the nutrients each soil cell regains per update
+    pub root_uptake_rate: f32,          // the max amount of nutrients a root cell draws from its soil cell per update
+    pub cell_nutrient_cost: f32,        // the nutrients needed to grow a leaf or trunk, roots only cost energy
+    pub seed_nutrients: f32,            // the nutrients each seed starts with
+    pub decay_nutrients: f32,           // the nutrients a decaying dead cell releases into the ground beneath it
+    pub litter_seep_rate: f32,          // the share of the litter on the ground which seeps into the top soil row per update
+    pub seed_litter_uptake: f32,        // the max amount of litter nutrients a grounded seed absorbs per update
+    pub rain_rate: f32,                 // the chance of rain in each update, lower values mean droughts
+    pub rain_amount: f32,               // the water each rain puts on the ground of every column
+    pub infiltration_rate: f32,         // the max amount of water which seeps from the ground into the soil per update
+    pub moisture_capacity: f32,         // the max amount of water a soil cell can hold
+    pub moisture_diffusion: f32,        // the share of the moisture difference which flows to each neighbor per update
+    pub evaporation_rate: f32,          // the share of the water on the ground and in the top soil row which evaporates
+    pub root_water_uptake: f32,         // the max amount of water a root cell draws from its soil cell per update
+    pub leaf_water_demand: f32,         // the water each leaf needs per update, 0 disables droughts
+    pub drought_energy_cost: f32,       // the energy a plant looses per missing unit of water
+    pub drought_leaf_loss: f32,         // the chance of a leaf to die, if the plant gets no water at all
+    pub wind_speed: f32,                // the cells airborne seeds get pushed per update at the top of the grid, negative blows to the left
+    pub wind_gust_strength: f32,        // gusts change the wind speed of each update by a random amount up to this value
+    pub wind_shear: f32,                // how much weaker the wind is near the ground, 1 means no wind at the ground at all
+    pub wing_glide: f32,                // seeds with full wings stay this many times longer in the air
+    pub wing_energy_cost: f32,          // the energy a seed with full wings has less at the start
+    pub seed_energy_drain: f32,         // the amount of energy each seed looses while staying dormant in the ground
+    pub dead_cell_remove_rate: f32,     // the chance that a dead cell disappears
+    pub seed_spawn_rate: f32,           // chance at which a dead cell becomes a seed
+    pub base_max_age: usize,            // the default max age of a plant
+    pub max_age_cell_modifier: usize,   // the amount each grown cell increases the lifetime of the plant
+    pub lifetime_factor: f32,           // how much the lifetime of a plant increases the energy of seeds
+    pub seed: Option<u64>,              // the seed for all randomness, a random one is chosen if not set, toml only holds seeds up to i64::MAX
+    pub species_threshold: f32,         // the genome distance at which plants belong to different species
+    pub species_interval: u64,          // the amount of updates between two species clusterings, 0 disables them
+    pub crossover: Crossover,           // how seeds get combined with a partner, none is asexual reproduction
+    pub crossover_points: usize,        // the amount of crossover points for multi_point crossover
+    pub pollination_range: i32,         // the max horizontal distance between the roots of two partners
+    pub pollination_rate: f32,          // chance that a seed gets pollinated, if there is a partner in range
+}
+
+impl Default for SimulationConfig {
+    fn default() -> Self {
+        Self {
+            grid_size: GRID_SIZE,
+            root_con_decay: ROOT_CON_DECAY,
+            trunk_absorb_rate: TRUNK_ABSORB_RATE,
+            leaf_absorb_rate: LEAF_ABSORB_RATE,
+            genome_size: GENOME_SIZE,
+            mutation_rate: MUTATION_RATE,
+            creep_rate: CREEP_RATE,
+            creep_size: CREEP_SIZE,
+            duplication_rate: DUPLICATION_RATE,
+            inversion_rate: INVERSION_RATE,
+            insertion_rate: INSERTION_RATE,
+            deletion_rate: DELETION_RATE,
+            max_segment_length: MAX_SEGMENT_LENGTH,
+            max_genome_length: MAX_GENOME_LENGTH,
+            response_size: RESPONSE_SIZE,
+            height_threshold_chance: HEIGHT_THRESHOLD_CHANCE,
+            cell_growth_cost: CELL_GROWTH_COST,
+            max_growths_per_iteration: MAX_GROWTHS_PER_ITERATION,
+            cell_sustain_energy_cost: CELL_SUSTAIN_ENERGY_COST,
+            default_energy: DEFAULT_ENERGY,
+            sun_power: SUN_POWER,
+            sun_angle: SUN_ANGLE,
+            day_length: DAY_LENGTH,
+            night_fraction: NIGHT_FRACTION,
+            sun_angle_sweep: SUN_ANGLE_SWEEP,
+            year_length: YEAR_LENGTH,
+            season_sun_amplitude: SEASON_SUN_AMPLITUDE,
+            season_sustain_amplitude: SEASON_SUSTAIN_AMPLITUDE,
+            germination_threshold: GERMINATION_THRESHOLD,
+            winter_threshold: WINTER_THRESHOLD,
+            winter_leaf_loss: WINTER_LEAF_LOSS,
+            trunk_load_capacity: TRUNK_LOAD_CAPACITY,
+            overhang_load_factor: OVERHANG_LOAD_FACTOR,
+            terrain_height: TERRAIN_HEIGHT,
+            terrain_hills: TERRAIN_HILLS,
+            terrain_cliffs: TERRAIN_CLIFFS,
+            soil_depth: SOIL_DEPTH,
+            soil_capacity: SOIL_CAPACITY,
+            soil_regeneration: SOIL_REGENERATION,
+            root_uptake_rate: ROOT_UPTAKE_RATE,
+            cell_nutrient_cost: CELL_NUTRIENT_COST,
+            seed_nutrients: SEED_NUTRIENTS,
+            decay_nutrients: DECAY_NUTRIENTS,
+            litter_seep_rate: LITTER_SEEP_RATE,
+            seed_litter_uptake: SEED_LITTER_UPTAKE,
+            rain_rate: RAIN_RATE,
+            rain_amount: RAIN_AMOUNT,
+            infiltration_rate: INFILTRATION_RATE,
+            moisture_capacity: MOISTURE_CAPACITY,
+            moisture_diffusion: MOISTURE_DIFFUSION,
+            evaporation_rate: EVAPORATION_RATE,
+            root_water_uptake: ROOT_WATER_UPTAKE,
+            leaf_water_demand: LEAF_WATER_DEMAND,
+            drought_energy_cost: DROUGHT_ENERGY_COST,
+            drought_leaf_loss: DROUGHT_LEAF_LOSS,
+            wind_speed: WIND_SPEED,
+            wind_gust_strength: WIND_GUST_STRENGTH,
+            wind_shear: WIND_SHEAR,
+            wing_glide: WING_GLIDE,
+            wing_energy_cost: WING_ENERGY_COST,
+            seed_energy_drain: SEED_ENERGY_DRAIN,
+            dead_cell_remove_rate: DEAD_CELL_REMOVE_RATE,
+            seed_spawn_rate: SEED_SPAWN_RATE,
+            base_max_age: BASE_MAX_AGE,
+            max_age_cell_modifier: MAX_AGE_CELL_MODIFIER,
+            lifetime_factor: LIFETIME_FACTOR,
+            seed: None,
+            species_threshold: SPECIES_THRESHOLD,
+            species_interval: SPECIES_INTERVAL,
+            crossover: CROSSOVER,
+            crossover_points: CROSSOVER_POINTS,
+            pollination_range: POLLINATION_RANGE,
+            pollination_rate: POLLINATION_RATE,
+        }
+    }
+}
+
+impl SimulationConfig {
+    pub fn with_grid_size(grid_size: IVec2) -> Self {
+        Self {
+            grid_size,
+            ..Default::default()
+        }
+    }
+
+    // loads a config from a .toml or .json file and validates it
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ConfigError> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)?;
+        let config: Self = match ConfigFormat::from_path(path)? {
+            ConfigFormat::Toml => toml::from_str(&content)
+                .map_err(|e| ConfigError::Parse(e.to_string()))?,
+            ConfigFormat::Json => serde_json::from_str(&content)
+                .map_err(|e| ConfigError::Parse(e.to_string()))?,
+        };
+        config.validate()?;
+        Ok(config)
+    }
+
+    // saves the config to a .toml or .json file, the format is chosen by the file extension,
+    // a seed above i64::MAX can only be saved as .json
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), ConfigError> {
+        let path = path.as_ref();
+        self.validate()?;
+        let content = match ConfigFormat::from_path(path)? {
+            ConfigFormat::Toml => toml::to_string_pretty(self)
+                .map_err(|e| ConfigError::Serialize(e.to_string()))?,
+            ConfigFormat::Json => serde_json::to_string_pretty(self)
+                .map_err(|e| ConfigError::Serialize(e.to_string()))?,
+        };
+        std::fs::write(path, content)?;
+        Ok(())
+    }
+
+    // checks every parameter for a sensible value, the error names the first offending key
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.grid_size.x <= 0 || self.grid_size.y <= 0 {
+            return Err(ConfigError::invalid("grid_size", "both dimensions have to be greater than 0"));
+        }
+        check_fraction("root_con_decay", self.root_con_decay as f64)?;
+        check_fraction("trunk_absorb_rate", self.trunk_absorb_rate as f64)?;
+        check_fraction("leaf_absorb_rate", self.leaf_absorb_rate as f64)?;
+        if self.genome_size == 0 || self.genome_size > self.max_genome_length {
+            return Err(ConfigError::invalid("genome_size", "has to be between 1 and max_genome_length"));
+        }
+        check_fraction("mutation_rate", self.mutation_rate)?;
+        check_fraction("creep_rate", self.creep_rate)?;
+        check_fraction("duplication_rate", self.duplication_rate)?;
+        check_fraction("inversion_rate", self.inversion_rate)?;
+        check_fraction("insertion_rate", self.insertion_rate)?;
+        check_fraction("deletion_rate", self.deletion_rate)?;
+        if self.max_segment_length == 0 {
+            return Err(ConfigError::invalid("max_segment_length", "has to be greater than 0"));
+        }
+        if self.max_genome_length == 0 {
+            return Err(ConfigError::invalid("max_genome_length", "has to be greater than 0"));
+        }
+        if self.response_size == 0 {
+            return Err(ConfigError::invalid("response_size", "has to be greater than 0"));
+        }
+        check_fraction("height_threshold_chance", self.height_threshold_chance as f64)?;
+        check_non_negative("cell_growth_cost", self.cell_growth_cost)?;
+        check_non_negative("cell_sustain_energy_cost", self.cell_sustain_energy_cost)?;
+        if self.default_energy <= 0. {
+            return Err(ConfigError::invalid("default_energy", "has to be greater than 0"));
+        }
+        check_non_negative("sun_power", self.sun_power)?;
+        // the sun can't shine horizontally at any time of the day, the sweep only matters with a day cycle
+        let sweep = if self.day_length > 0 { self.sun_angle_sweep.abs() } else { 0. };
+        if self.sun_angle.abs() + sweep >= 90. {
+            return Err(ConfigError::invalid("sun_angle", "together with sun_angle_sweep it has to stay below 90 degrees"));
+        }
+        if !(0.0..1.0).contains(&self.night_fraction) {
+            return Err(ConfigError::invalid("night_fraction", format!("has to be at least 0 and below 1, got {}", self.night_fraction)));
+        }
+        check_fraction("season_sun_amplitude", self.season_sun_amplitude as f64)?;
+        check_fraction("season_sustain_amplitude", self.season_sustain_amplitude as f64)?;
+        check_season("germination_threshold", self.germination_threshold)?;
+        check_season("winter_threshold", self.winter_threshold)?;
+        check_fraction("winter_leaf_loss", self.winter_leaf_loss as f64)?;
+        check_non_negative("trunk_load_capacity", self.trunk_load_capacity)?;
+        // new seeds appear in the top row, which has to stay above the ground
+        if self.terrain_height < 0 || (self.terrain_height > 0 && self.terrain_height >= self.grid_size.y - 1) {
+            return Err(ConfigError::invalid("terrain_height", "has to be at least 0 and below the grid height minus 1"));
+        }
+        if self.soil_depth < 0 {
+            return Err(ConfigError::invalid("soil_depth", "can not be negative"));
+        }
+        check_non_negative("soil_capacity", self.soil_capacity)?;
+        check_non_negative("soil_regeneration", self.soil_regeneration)?;
+        check_non_negative("root_uptake_rate", self.root_uptake_rate)?;
+        check_non_negative("cell_nutrient_cost", self.cell_nutrient_cost)?;
+        check_non_negative("seed_nutrients", self.seed_nutrients)?;
+        check_non_negative("decay_nutrients", self.decay_nutrients)?;
+        check_fraction("litter_seep_rate", self.litter_seep_rate as f64)?;
+        check_non_negative("seed_litter_uptake", self.seed_litter_uptake)?;
+        check_fraction("rain_rate", self.rain_rate as f64)?;
+        check_non_negative("rain_amount", self.rain_amount)?;
+        check_non_negative("infiltration_rate", self.infiltration_rate)?;
+        check_non_negative("moisture_capacity", self.moisture_capacity)?;
+        // a cell has up to 4 neighbors, more would let it give away more water than it holds
+        if !(0.0..=0.25).contains(&self.moisture_diffusion) {
+            return Err(ConfigError::invalid("moisture_diffusion", format!("has to be between 0 and 0.25, got {}", self.moisture_diffusion)));
+        }
+        check_fraction("evaporation_rate", self.evaporation_rate as f64)?;
+        check_non_negative("root_water_uptake", self.root_water_uptake)?;
+        check_non_negative("leaf_water_demand", self.leaf_water_demand)?;
+        check_non_negative("drought_energy_cost", self.drought_energy_cost)?;
+        check_fraction("drought_leaf_loss", self.drought_leaf_loss as f64)?;
+        if self.overhang_load_factor < 1. {
+            return Err(ConfigError::invalid("overhang_load_factor", format!("has to be at least 1, got {}", self.overhang_load_factor)));
+        }
+        check_non_negative("wind_gust_strength", self.wind_gust_strength)?;
+        check_fraction("wind_shear", self.wind_shear as f64)?;
+        check_non_negative("wing_glide", self.wing_glide)?;
+        check_non_negative("wing_energy_cost", self.wing_energy_cost)?;
+        check_non_negative("seed_energy_drain", self.seed_energy_drain)?;
+        check_fraction("dead_cell_remove_rate", self.dead_cell_remove_rate as f64)?;
+        check_fraction("seed_spawn_rate", self.seed_spawn_rate as f64)?;
+        check_non_negative("lifetime_factor", self.lifetime_factor)?;
+        check_fraction("species_threshold", self.species_threshold as f64)?;
+        if self.crossover == Crossover::MultiPoint && self.crossover_points == 0 {
+            return Err(ConfigError::invalid("crossover_points", "multi_point crossover needs at least 1 point"));
+        }
+        if self.pollination_range < 0 {
+            return Err(ConfigError::invalid("pollination_range", "can not be negative"));
+        }
+        check_fraction("pollination_rate", self.pollination_rate as f64)?;
+        Ok(())
+    }
+}
+
+fn check_fraction(key: &'static str, value: f64) -> Result<(), ConfigError> {
+    if (0.0..=1.0).contains(&value) {
+        Ok(())
+    } else {
+        Err(ConfigError::invalid(key, format!("has to be between 0 and 1, got {value}")))
+    }
+}
+
+// seasons range from -1 (midwinter) to 1 (midsummer)
+fn check_season(key: &'static str, value: f32) -> Result<(), ConfigError> {
+    if (-1.0..=1.0).contains(&value) {
+        Ok(())
+    } else {
+        Err(ConfigError::invalid(key, format!("has to be between -1 and 1, got {value}")))
+    }
+}
+
+fn check_non_negative(key: &'static str, value: f32) -> Result<(), ConfigError> {
+    if value >= 0. {
+        Ok(())
+    } else {
+        Err(ConfigError::invalid(key, format!("can not be negative, got {value}")))
+    }
+}
+
+enum ConfigFormat {
+    Toml,
+    Json,
+}
+
+impl ConfigFormat {
+    fn from_path(path: &Path) -> Result<Self, ConfigError> {
+        match path.extension().and_then(|x| x.to_str()) {
+            Some("toml") => Ok(ConfigFormat::Toml),
+            Some("json") => Ok(ConfigFormat::Json),
+            _ => Err(ConfigError::UnknownFormat(path.display().to_string())),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(std::io::Error),
+    Parse(String),
+    Serialize(String),
+    UnknownFormat(String),
+    Invalid { key: &'static str, reason: String },
+}
+
+impl ConfigError {
+    fn invalid(key: &'static str, reason: impl Into<String>) -> Self {
+        ConfigError::Invalid { key, reason: reason.into() }
+    }
+}
+
+impl Display for ConfigError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigError::Io(e) => write!(f, "could not access config file: {e}"),
+            ConfigError::Parse(e) => write!(f, "could not parse config: {e}"),
+            ConfigError::Serialize(e) => write!(f, "could not serialize config: {e}"),
+            ConfigError::UnknownFormat(path) =>
+                write!(f, "unknown config format of '{path}', expected a .toml or .json file"),
+            ConfigError::Invalid { key, reason } => write!(f, "invalid value for '{key}': {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl From<std::io::Error> for ConfigError {
+    fn from(e: std::io::Error) -> Self {
+        ConfigError::Io(e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn large_seeds_only_save_as_json() {
+        let config = SimulationConfig { seed: Some(u64::MAX), ..Default::default() };
+        let path = std::env::temp_dir().join(format!("treevolution_config_{}", std::process::id()));
+        let toml_path = path.with_extension("toml");
+        assert!(matches!(config.save(&toml_path), Err(ConfigError::Serialize(_))));
+        assert!(!toml_path.exists());
+
+        let json_path = path.with_extension("json");
+        config.save(&json_path).unwrap();
+        let loaded = SimulationConfig::load(&json_path).unwrap();
+        std::fs::remove_file(&json_path).unwrap();
+        assert_eq!(loaded.seed, Some(u64::MAX));
+    }
+}
@@ -0,0 +1,126 @@
+use std::fmt::{Display, Formatter};
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Write};
+use std::path::Path;
+use serde::{Deserialize, Serialize};
+use crate::sim_mod::habitat::Habitat;
+
+// has to be increased whenever the serialized layout of the habitat changes,
+// snapshots of other versions are rejected on load
+pub const SNAPSHOT_VERSION: u32 = 14;
+
+#[derive(Serialize)]
+struct SnapshotRef<'a> {
+    version: u32,
+    habitat: &'a Habitat,
+}
+
+#[derive(Deserialize)]
+struct SnapshotHeader {
+    version: u32,
+}
+
+#[derive(Deserialize)]
+struct Snapshot {
+    habitat: Habitat,
+}
+
+impl Habitat {
+    // saves the complete state of the habitat as json, including config and rng state,
+    // so that a loaded habitat continues exactly like the saved one would have
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), SnapshotError> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        serde_json::to_writer(&mut writer, &SnapshotRef { version: SNAPSHOT_VERSION, habitat: self })
+            .map_err(|e| SnapshotError::Parse(e.to_string()))?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Habitat, SnapshotError> {
+        let value: serde_json::Value = serde_json::from_reader(BufReader::new(File::open(path)?))
+            .map_err(|e| SnapshotError::Parse(e.to_string()))?;
+        // check the version first, so an outdated snapshot doesn't show up as a parse error
+        let header = SnapshotHeader::deserialize(&value)
+            .map_err(|e| SnapshotError::Parse(e.to_string()))?;
+        if header.version != SNAPSHOT_VERSION {
+            return Err(SnapshotError::UnsupportedVersion(header.version));
+        }
+        let snapshot = Snapshot::deserialize(value)
+            .map_err(|e| SnapshotError::Parse(e.to_string()))?;
+        Ok(snapshot.habitat)
+    }
+}
+
+#[derive(Debug)]
+pub enum SnapshotError {
+    Io(std::io::Error),
+    Parse(String),
+    UnsupportedVersion(u32),
+}
+
+impl Display for SnapshotError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SnapshotError::Io(e) => write!(f, "could not access snapshot file: {e}"),
+            SnapshotError::Parse(e) => write!(f, "could not parse snapshot: {e}"),
+            SnapshotError::UnsupportedVersion(version) =>
+                write!(f, "snapshot version {version} is not supported, expected version {SNAPSHOT_VERSION}"),
+        }
+    }
+}
+
+impl std::error::Error for SnapshotError {}
+
+impl From<std::io::Error> for SnapshotError {
+    fn from(e: std::io::Error) -> Self {
+        SnapshotError::Io(e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::IVec2;
+    use super::*;
+    use crate::sim_mod::simulation_config::SimulationConfig;
+
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("treevolution_{}_{name}.json", std::process::id()))
+    }
+
+    #[test]
+    fn loaded_habitat_continues_like_the_saved_one() {
+        let config = SimulationConfig {
+            soil_depth: 4,
+            terrain_height: 4,
+            year_length: 100,
+            wind_gust_strength: 1.,
+            ..SimulationConfig::with_grid_size(IVec2::new(48, 20))
+        };
+        let mut habitat = Habitat::with_seed(config, 3).unwrap();
+        habitat.set_minimum_plants(10);
+        for _ in 0..200 {
+            habitat.update();
+        }
+        let path = temp_path("round_trip");
+        habitat.save(&path).unwrap();
+        let mut loaded = Habitat::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(serde_json::to_string(&habitat).unwrap(), serde_json::to_string(&loaded).unwrap());
+
+        for _ in 0..200 {
+            habitat.update();
+            loaded.update();
+        }
+        assert!(habitat.get_living_plant_count() > 0);
+        assert_eq!(serde_json::to_string(&habitat).unwrap(), serde_json::to_string(&loaded).unwrap());
+    }
+
+    #[test]
+    fn other_versions_are_rejected() {
+        let path = temp_path("old_version");
+        std::fs::write(&path, r#"{"version": 0, "habitat": {}}"#).unwrap();
+        let result = Habitat::load(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert!(matches!(result, Err(SnapshotError::UnsupportedVersion(0))));
+    }
+}
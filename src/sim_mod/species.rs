@@ -0,0 +1,73 @@
+use serde::{Deserialize, Serialize};
+use crate::sim_mod::genome::Genome;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SpeciesInfo {
+    pub id: u64,
+    pub size: usize,            // the amount of living plants in the species at the last clustering
+    pub first_seen_tick: u64,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Species {
+    info: SpeciesInfo,
+    representative: Genome,     // new plants get compared against this genome
+}
+
+// groups living plants into species by the distance of their genomes,
+// species keep their id as long as there are plants close enough to their representative
+#[derive(Default, Serialize, Deserialize)]
+pub struct SpeciesTracker {
+    species: Vec<Species>,
+    next_species_id: u64,
+}
+
+impl SpeciesTracker {
+    // assigns a species id to every genome, in the same order as the given genomes
+    // every genome joins the first species whose representative is closer than the threshold,
+    // otherwise it founds a new species. Afterward the first member of each species becomes its new
+    // representative and species without members die out
+    pub fn cluster(&mut self, genomes: &[&Genome], threshold: f32, tick: u64) -> Vec<u64> {
+        let mut assignments = Vec::with_capacity(genomes.len());
+        let mut sizes = vec![0usize; self.species.len()];
+        let mut new_representatives: Vec<Option<&Genome>> = vec![None; self.species.len()];
+
+        for genome in genomes {
+            let species_ix = match self.species.iter()
+                .position(|species| species.representative.distance(genome) < threshold) {
+                Some(ix) => ix,
+                None => {
+                    self.species.push(Species {
+                        info: SpeciesInfo { id: self.next_species_id, size: 0, first_seen_tick: tick },
+                        representative: (*genome).clone(),
+                    });
+                    self.next_species_id += 1;
+                    sizes.push(0);
+                    new_representatives.push(None);
+                    self.species.len() - 1
+                }
+            };
+            sizes[species_ix] += 1;
+            new_representatives[species_ix].get_or_insert(genome);
+            assignments.push(self.species[species_ix].info.id);
+        }
+
+        for (ix, species) in self.species.iter_mut().enumerate() {
+            species.info.size = sizes[ix];
+            if let Some(genome) = new_representatives[ix] {
+                species.representative = genome.clone();
+            }
+        }
+        self.species.retain(|species| species.info.size > 0);
+        assignments
+    }
+
+    // all species which had members at the last clustering
+    pub fn get_species(&self) -> Vec<SpeciesInfo> {
+        self.species.iter().map(|species| species.info.clone()).collect()
+    }
+
+    pub fn get_species_count(&self) -> usize {
+        self.species.len()
+    }
+}
@@ -0,0 +1,126 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use crate::sim_mod::habitat_stats::HabitatStats;
+
+const CSV_HEADER: &str = "tick,living_plants,airborne_seeds,grounded_seeds,total_energy,mean_energy,\
+max_energy,mean_plant_height,species,leaf_cells,trunk_cells,root_cells,absorbed_sunlight,births,deaths,season,broken_cells,soil_nutrients,\
+litter_nutrients,soil_moisture,drought_plants,wind,mean_wing_size";
+
+// appends the stats of every n-th update to a csv file, attached to a habitat with Habitat::set_recorder
+// births, deaths and broken cells are summed up over the whole sampling interval, all other values are taken at the sampled tick,
+// finishing the recording also writes the last update if it wasn't sampled
+pub struct StatsRecorder {
+    writer: BufWriter<File>,
+    interval: u64,
+    births: usize,
+    deaths: usize,
+    broken_cells: usize,
+    unsampled: Option<HabitatStats>,    // the last update, as long as it wasn't written
+    error: Option<std::io::Error>,
+}
+
+impl StatsRecorder {
+    // creates (or truncates) the csv file and writes the header, the interval has to be greater than 0
+    pub fn create(path: impl AsRef<Path>, interval: u64) -> std::io::Result<Self> {
+        if interval == 0 {
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, "the sampling interval has to be greater than 0"));
+        }
+        let mut writer = BufWriter::new(File::create(path)?);
+        writeln!(writer, "{CSV_HEADER}")?;
+        Ok(Self {
+            writer,
+            interval,
+            births: 0,
+            deaths: 0,
+            broken_cells: 0,
+            unsampled: None,
+            error: None,
+        })
+    }
+
+    // called by the habitat after every update, the first write error stops the recording
+    pub fn record(&mut self, stats: &HabitatStats) {
+        if self.error.is_some() {
+            return;
+        }
+        self.births += stats.births;
+        self.deaths += stats.deaths;
+        self.broken_cells += stats.broken_cells;
+        if !stats.tick.is_multiple_of(self.interval) {
+            self.unsampled = Some(stats.clone());
+            return;
+        }
+        self.unsampled = None;
+        self.write_sample(stats);
+    }
+
+    fn write_sample(&mut self, stats: &HabitatStats) {
+        if let Err(e) = self.write_row(stats) {
+            self.error = Some(e);
+        }
+        self.births = 0;
+        self.deaths = 0;
+        self.broken_cells = 0;
+    }
+
+    fn write_row(&mut self, stats: &HabitatStats) -> std::io::Result<()> {
+        writeln!(self.writer, "{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{}",
+                 stats.tick, stats.living_plants, stats.airborne_seeds, stats.grounded_seeds,
+                 stats.total_energy, stats.mean_energy, stats.max_energy, stats.mean_plant_height,
+                 stats.species, stats.leaf_cells, stats.trunk_cells, stats.root_cells, stats.absorbed_sunlight, self.births, self.deaths,
+                 stats.season, self.broken_cells, stats.soil_nutrients, stats.litter_nutrients, stats.soil_moisture, stats.drought_plants,
+                 stats.wind, stats.mean_wing_size)
+    }
+
+    // writes the last update if it wasn't sampled and flushes all buffered rows,
+    // returns the first error which happened while recording
+    pub fn finish(mut self) -> std::io::Result<()> {
+        if let Some(stats) = self.unsampled.take() {
+            if self.error.is_none() {
+                self.write_sample(&stats);
+            }
+        }
+        if let Some(e) = self.error.take() {
+            return Err(e);
+        }
+        self.writer.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn recorded_ticks(ticks: u64, interval: u64) -> Vec<(String, String)> {
+        let path = std::env::temp_dir().join(format!("treevolution_stats_{ticks}_{}.csv", std::process::id()));
+        let mut recorder = StatsRecorder::create(&path, interval).unwrap();
+        for tick in 1..=ticks {
+            recorder.record(&HabitatStats { tick, births: 1, ..Default::default() });
+        }
+        recorder.finish().unwrap();
+        let csv = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        // the tick and the births of every row
+        csv.lines().skip(1)
+            .map(|line| {
+                let values: Vec<&str> = line.split(',').collect();
+                (values[0].to_string(), values[13].to_string())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn last_update_gets_written_when_finishing() {
+        let rows = recorded_ticks(250, 100);
+        let expected = [("100", "100"), ("200", "100"), ("250", "50")];
+        assert_eq!(rows, expected.map(|(tick, births)| (tick.to_string(), births.to_string())));
+        assert_eq!(recorded_ticks(200, 100).len(), 2);
+    }
+
+    #[test]
+    fn zero_interval_is_rejected() {
+        let path = std::env::temp_dir().join("treevolution_stats_zero.csv");
+        assert!(StatsRecorder::create(path, 0).is_err());
+    }
+}
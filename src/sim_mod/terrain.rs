@@ -0,0 +1,38 @@
+use std::f32::consts::TAU;
+use rand::Rng;
+use crate::sim_mod::simulation_config::SimulationConfig;
+
+// the amount of overlaid waves, each one with a higher frequency and a lower amplitude than the last one
+const WAVE_COUNT: usize = 4;
+
+// generates the ground height of every column between 0 and terrain_height,
+// hills are a sum of waves which wrap around the grid, plateaus and basins add cliffs on both of their sides
+pub fn generate_terrain(config: &SimulationConfig, rng: &mut impl Rng) -> Vec<i32> {
+    let width = config.grid_size.x as usize;
+    if config.terrain_height == 0 {
+        return vec![0; width];
+    }
+    let max_height = config.terrain_height as f32;
+    let waves: Vec<(f32, f32)> = (1..=WAVE_COUNT)
+        .map(|k| (k as f32, rng.random_range(0. ..TAU)))
+        .collect();
+    let total_amplitude: f32 = waves.iter().map(|(k, _)| 1. / k).sum();
+    let mut heights: Vec<f32> = (0..width)
+        .map(|x| {
+            let angle = x as f32 / width as f32 * TAU * config.terrain_hills as f32;
+            let wave: f32 = waves.iter().map(|(k, phase)| (angle * k + phase).sin() / k).sum();
+            (wave / total_amplitude + 1.) / 2. * max_height
+        })
+        .collect();
+    for _ in 0..config.terrain_cliffs {
+        let start = rng.random_range(0..width);
+        let length = rng.random_range(1..=(width / 4).max(1));
+        let step = rng.random_range(-config.terrain_height..=config.terrain_height) as f32;
+        for x in start..start + length {
+            heights[x % width] += step;
+        }
+    }
+    heights.into_iter()
+        .map(|height| height.round().clamp(0., max_height) as i32)
+        .collect()
+}